@@ -0,0 +1,278 @@
+// --- Day 2: Password Philosophy--
+//
+// Pieces shared by the day 2 solutions.
+use std::error::Error;
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+pub mod stream;
+
+/// One line of the puzzle input: `low-high c: password`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PwdEntry {
+    pub low: usize,
+    pub high: usize,
+    pub pat: char,
+    pub pwd: String,
+}
+
+impl PwdEntry {
+    pub fn new(tup: (usize, usize, char, String)) -> PwdEntry {
+        PwdEntry {
+            low: tup.0,
+            high: tup.1,
+            pat: tup.2,
+            pwd: tup.3,
+        }
+    }
+
+    /// Part one: `pat` occurs between `low` and `high` times.
+    pub fn count_policy(&self) -> bool {
+        let count = self.pwd.chars().filter(|&c| c == self.pat).count();
+        (self.low..=self.high).contains(&count)
+    }
+
+    /// Part two: `pat` is at exactly one of positions `low` and `high`.
+    pub fn position_policy(&self, indexing: Indexing) -> bool {
+        exactly_one_at(&self.pwd, self.low, self.high, self.pat, indexing)
+    }
+}
+
+/// Why a line could not be read as a `PwdEntry`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseEntryError {
+    /// The line stopped early or had the wrong character at byte `pos`.
+    Syntax { pos: usize, expected: &'static str },
+    /// A policy bound does not fit in a `usize`.
+    Number(ParseIntError),
+    /// Policy bounds are 1-based, so zero is never meaningful.
+    ZeroBound,
+    /// `low` is greater than `high`, as in `3-1 a: x`.
+    Reversed { low: usize, high: usize },
+    /// The policy letter was more than one character, e.g. `ab:`.
+    Letter(String),
+}
+
+impl fmt::Display for ParseEntryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseEntryError::Syntax { pos, expected } => {
+                write!(f, "expected {} at byte {}", expected, pos)
+            }
+            ParseEntryError::Number(e) => write!(f, "invalid policy bound: {}", e),
+            ParseEntryError::ZeroBound => f.write_str("policy bounds start at 1"),
+            ParseEntryError::Reversed { low, high } => {
+                write!(f, "policy low {} is greater than high {}", low, high)
+            }
+            ParseEntryError::Letter(s) => {
+                write!(f, "policy letter {:?} is not a single character", s)
+            }
+        }
+    }
+}
+
+impl Error for ParseEntryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseEntryError::Number(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// A cursor over the line being parsed, tracking the byte offset for errors.
+struct Cursor<'a> {
+    line: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn rest(&self) -> &'a str {
+        &self.line[self.pos..]
+    }
+
+    fn error(&self, expected: &'static str) -> ParseEntryError {
+        ParseEntryError::Syntax {
+            pos: self.pos,
+            expected,
+        }
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, f: F) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn expect(&mut self, c: char, expected: &'static str) -> Result<(), ParseEntryError> {
+        if self.rest().starts_with(c) {
+            self.pos += c.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(expected))
+        }
+    }
+
+    fn number(&mut self) -> Result<usize, ParseEntryError> {
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        if digits.is_empty() {
+            return Err(self.error("a number"));
+        }
+        match digits.parse() {
+            Ok(0) => Err(ParseEntryError::ZeroBound),
+            Ok(n) => Ok(n),
+            Err(e) => Err(ParseEntryError::Number(e)),
+        }
+    }
+}
+
+impl FromStr for PwdEntry {
+    type Err = ParseEntryError;
+
+    /// Parses `low-high c: password`, the only shape the puzzle uses.
+    fn from_str(line: &str) -> Result<PwdEntry, ParseEntryError> {
+        let mut cur = Cursor { line, pos: 0 };
+
+        let low = cur.number()?;
+        cur.expect('-', "'-'")?;
+        let high = cur.number()?;
+        cur.expect(' ', "' '")?;
+
+        let letter = cur.take_while(|c| c != ':' && !c.is_whitespace());
+        let mut chars = letter.chars();
+        let pat = match (chars.next(), chars.next()) {
+            (Some(c), None) => c,
+            (None, _) => return Err(cur.error("a policy letter")),
+            (Some(_), Some(_)) => return Err(ParseEntryError::Letter(letter.to_string())),
+        };
+        cur.expect(':', "':'")?;
+        cur.expect(' ', "' '")?;
+
+        let pwd = cur.take_while(|c| !c.is_whitespace());
+        if pwd.is_empty() {
+            return Err(cur.error("a password"));
+        }
+        if !cur.rest().is_empty() {
+            return Err(cur.error("end of line"));
+        }
+
+        if low > high {
+            return Err(ParseEntryError::Reversed { low, high });
+        }
+        Ok(PwdEntry::new((low, high, pat, pwd.to_string())))
+    }
+}
+
+impl fmt::Display for PwdEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{} {}: {}", self.low, self.high, self.pat, self.pwd)
+    }
+}
+
+/// How the 1-based positions of a password policy are counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Indexing {
+    /// Positions count `char`s, so `é` or `日` is a single position.
+    #[default]
+    Chars,
+    /// Positions count bytes. Only matches `Chars` for ASCII passwords.
+    Bytes,
+}
+
+/// Whether the letter at 1-based position `pos` of `pwd` is `letter`.
+///
+/// With `Indexing::Bytes` a position falling inside a multibyte
+/// character never matches.
+pub fn letter_at(pwd: &str, pos: usize, letter: char, indexing: Indexing) -> bool {
+    if pos == 0 {
+        return false;
+    }
+    match indexing {
+        Indexing::Chars => pwd.chars().nth(pos - 1) == Some(letter),
+        Indexing::Bytes => pwd
+            .get(pos - 1..)
+            .is_some_and(|rest| rest.starts_with(letter)),
+    }
+}
+
+/// The part two policy: exactly one of `first` and `second` holds `letter`.
+pub fn exactly_one_at(
+    pwd: &str,
+    first: usize,
+    second: usize,
+    letter: char,
+    indexing: Indexing,
+) -> bool {
+    letter_at(pwd, first, letter, indexing) != letter_at(pwd, second, letter, indexing)
+}
+
+#[test]
+fn test_ascii_positions() {
+    for &indexing in &[Indexing::Chars, Indexing::Bytes] {
+        assert!(exactly_one_at("abcde", 1, 3, 'a', indexing));
+        assert!(!exactly_one_at("cdefg", 1, 3, 'b', indexing));
+        assert!(!exactly_one_at("ccccccccc", 2, 9, 'c', indexing));
+    }
+}
+
+#[test]
+fn test_multibyte_positions() {
+    // 'é' is two bytes, so the third char sits at byte 4.
+    assert!(letter_at("héllo", 3, 'l', Indexing::Chars));
+    assert!(!letter_at("héllo", 3, 'l', Indexing::Bytes));
+    assert!(letter_at("héllo", 4, 'l', Indexing::Bytes));
+    assert!(letter_at("héllo", 2, 'é', Indexing::Bytes));
+    // Byte 3 is the middle of 'é'.
+    assert!(!letter_at("héllo", 3, 'é', Indexing::Bytes));
+
+    assert!(exactly_one_at("日本日", 1, 2, '日', Indexing::Chars));
+    assert!(!exactly_one_at("日本日", 1, 3, '日', Indexing::Chars));
+    assert!(exactly_one_at("日本日", 1, 3, '日', Indexing::Bytes));
+    assert!(!letter_at("ab", 3, 'b', Indexing::Chars));
+    assert!(!letter_at("ab", 0, 'a', Indexing::Chars));
+}
+
+#[test]
+fn test_parse_round_trip() {
+    for line in &[
+        "1-3 a: abcde",
+        "2-9 c: ccccccccc",
+        "10-12 é: héllo",
+        "4-4 日: 日本",
+    ] {
+        let entry: PwdEntry = line.parse().unwrap();
+        assert_eq!(&entry.to_string(), line);
+    }
+    assert_eq!(
+        "1-3 b: cdefg".parse(),
+        Ok(PwdEntry::new((1, 3, 'b', "cdefg".to_string())))
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    let syntax = |pos, expected| Err(ParseEntryError::Syntax { pos, expected });
+    let parse = |s: &str| s.parse::<PwdEntry>();
+
+    assert_eq!(parse(""), syntax(0, "a number"));
+    assert_eq!(parse("1 3 a: x"), syntax(1, "'-'"));
+    assert_eq!(parse("1-3 a x"), syntax(5, "':'"));
+    assert_eq!(parse("1-3 : x"), syntax(4, "a policy letter"));
+    assert_eq!(parse("1-3 a: "), syntax(7, "a password"));
+    assert_eq!(parse("1-3 a: x y"), syntax(8, "end of line"));
+    assert_eq!(parse("0-3 a: x"), Err(ParseEntryError::ZeroBound));
+    assert_eq!(
+        parse("3-1 a: x"),
+        Err(ParseEntryError::Reversed { low: 3, high: 1 })
+    );
+    assert_eq!(
+        parse("1-3 ab: x"),
+        Err(ParseEntryError::Letter("ab".to_string()))
+    );
+    assert!(matches!(
+        parse("1-99999999999999999999999 a: x"),
+        Err(ParseEntryError::Number(_))
+    ));
+}
@@ -4,27 +4,12 @@
 use std::fs::File;
 use std::io::{self, BufRead};
 
-struct PwdEntry {
-    low: i32,
-    high: i32,
-    pat: char,
-    pwd: String,
-}
+use day_2::stream::{self, Options};
+use day_2::{exactly_one_at, Indexing, PwdEntry};
 
 enum ValidationAlgo {
     One,
-    Two,
-}
-
-impl PwdEntry {
-    fn new(tup: (i32, i32, char, String)) -> PwdEntry {
-        PwdEntry {
-            low: tup.0,
-            high: tup.1,
-            pat: tup.2,
-            pwd: tup.3,
-        }
-    }
+    Two(Indexing),
 }
 
 fn valid_passwords(passwords: &[PwdEntry]) -> i32 {
@@ -40,7 +25,7 @@ fn valid_passwords(passwords: &[PwdEntry]) -> i32 {
 fn valid_passwords2(passwords: &[PwdEntry]) -> i32 {
     let mut valid = 0;
     for entry in passwords {
-        if is_valid(entry, ValidationAlgo::Two) {
+        if is_valid(entry, ValidationAlgo::Two(Indexing::Chars)) {
             valid += 1;
         }
     }
@@ -49,28 +34,33 @@ fn valid_passwords2(passwords: &[PwdEntry]) -> i32 {
 fn is_valid(entry: &PwdEntry, algo: ValidationAlgo) -> bool {
     match algo {
         ValidationAlgo::One => {
-            let m = entry.pwd.matches(entry.pat).count() as i32;
-            if m >= entry.low && m <= entry.high {
-                true
-            } else {
-                false
-            }
+            let m = entry.pwd.matches(entry.pat).count();
+            (entry.low..=entry.high).contains(&m)
         }
-        ValidationAlgo::Two => {
-            let m: Vec<_> = entry.pwd.match_indices(entry.pat).map(|x| x.0).collect();
-            let low: usize = entry.low as usize - 1;
-            let high: usize = entry.high as usize - 1;
-            if m.contains(&low) && m.contains(&high) {
-                false
-            } else if m.contains(&low) || m.contains(&high) {
-                true
-            } else {
-                false
-            }
+        ValidationAlgo::Two(indexing) => {
+            exactly_one_at(&entry.pwd, entry.low, entry.high, entry.pat, indexing)
         }
     }
 }
 fn main() {
+    // `matt <dump> [threads]` streams a dump of any size instead.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(path) = args.first() {
+        let threads = args
+            .get(1)
+            .map_or(1, |t| t.parse().expect("Invalid thread count"));
+        let file = File::open(path).unwrap();
+        let options = Options {
+            threads,
+            ..Options::default()
+        };
+        println!(
+            "{}",
+            stream::validate(io::BufReader::new(file), options).unwrap()
+        );
+        return;
+    }
+
     let input = input().unwrap();
 
     println!("Part One: {}", valid_passwords(&input));
@@ -83,29 +73,18 @@ fn input() -> io::Result<Vec<PwdEntry>> {
     let buf = io::BufReader::new(file);
     for item in buf.lines() {
         let line = item?;
-        input.push(parse_input_line(&line).unwrap())
+        let entry = line.parse().map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{:?}: {}", line, e))
+        })?;
+        input.push(entry)
     }
     Ok(input)
 }
 
-fn parse_input_line(line: &str) -> Result<PwdEntry, std::num::ParseIntError> {
-    let parts: Vec<_> = line.split(' ').collect();
-    let low_high: Vec<_> = parts[0].split('-').collect();
-    let low: i32 = low_high[0].parse()?;
-    let high: i32 = low_high[1].parse()?;
-    let chr: char = parts[1].split(':').collect::<Vec<_>>()[0]
-        .chars()
-        .next()
-        .unwrap();
-    let pwd = parts[2].to_string();
-
-    Ok(PwdEntry::new((low, high, chr, pwd)))
-}
-
 #[test]
 fn test_matt() {
     assert_eq!(
-        valid_passwords(&vec![
+        valid_passwords(&[
             PwdEntry::new((1, 3, 'a', "abcde".to_string())),
             PwdEntry::new((1, 3, 'b', "cdefg".to_string())),
             PwdEntry::new((2, 9, 'c', "ccccccccc".to_string()))
@@ -113,7 +92,7 @@ fn test_matt() {
         2
     );
     assert_eq!(
-        valid_passwords2(&vec![
+        valid_passwords2(&[
             PwdEntry::new((1, 3, 'a', "abcde".to_string())),
             PwdEntry::new((1, 3, 'b', "cdefg".to_string())),
             PwdEntry::new((2, 9, 'c', "ccccccccc".to_string()))
@@ -121,3 +100,20 @@ fn test_matt() {
         1
     );
 }
+
+#[test]
+fn test_matt_unicode() {
+    let entries = vec![
+        PwdEntry::new((2, 3, 'ß', "aßßc".to_string())),
+        PwdEntry::new((1, 3, 'ü', "üxü".to_string())),
+        PwdEntry::new((3, 4, 'n', "päna".to_string())),
+    ];
+    assert_eq!(valid_passwords(&entries), 2);
+    assert_eq!(valid_passwords2(&entries), 1);
+    // Byte offsets land inside the multibyte letters instead.
+    let by_bytes = entries
+        .iter()
+        .filter(|e| is_valid(e, ValidationAlgo::Two(Indexing::Bytes)))
+        .count();
+    assert_eq!(by_bytes, 3);
+}
@@ -0,0 +1,298 @@
+// Validating password dumps line by line, without loading them into memory.
+use std::fmt;
+use std::io::{self, BufRead};
+use std::ops::AddAssign;
+use std::str;
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::{Indexing, PwdEntry};
+
+/// How `validate` reads its input.
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Worker threads; `0` or `1` validates on the reading thread.
+    pub threads: usize,
+    /// Lines handed to a worker at a time.
+    pub chunk_lines: usize,
+    /// How the position policy counts positions.
+    pub indexing: Indexing,
+    /// The longest line kept, in bytes. Longer lines are skipped without
+    /// being read into memory and counted as malformed.
+    pub max_line_bytes: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            threads: 1,
+            chunk_lines: 4096,
+            indexing: Indexing::Chars,
+            max_line_bytes: 64 * 1024,
+        }
+    }
+}
+
+/// Per-policy counts for a whole dump.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Tally {
+    /// Lines that parsed as a `PwdEntry`.
+    pub entries: usize,
+    /// Non-blank lines that did not parse, were not UTF-8 or were too long.
+    pub malformed: usize,
+    /// Entries passing the part one (count) policy.
+    pub count_policy: usize,
+    /// Entries passing the part two (position) policy.
+    pub position_policy: usize,
+}
+
+impl Tally {
+    fn add_line(&mut self, line: &[u8], indexing: Indexing) {
+        let line = match str::from_utf8(line) {
+            Ok(line) => line.trim_end_matches(&['\n', '\r'][..]),
+            Err(_) => {
+                self.malformed += 1;
+                return;
+            }
+        };
+        if line.is_empty() {
+            return;
+        }
+        match line.parse::<PwdEntry>() {
+            Ok(entry) => {
+                self.entries += 1;
+                self.count_policy += entry.count_policy() as usize;
+                self.position_policy += entry.position_policy(indexing) as usize;
+            }
+            Err(_) => self.malformed += 1,
+        }
+    }
+}
+
+impl AddAssign for Tally {
+    fn add_assign(&mut self, other: Tally) {
+        self.entries += other.entries;
+        self.malformed += other.malformed;
+        self.count_policy += other.count_policy;
+        self.position_policy += other.position_policy;
+    }
+}
+
+impl fmt::Display for Tally {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Entries:         {}", self.entries)?;
+        writeln!(f, "Malformed:       {}", self.malformed)?;
+        writeln!(f, "Count policy:    {}", self.count_policy)?;
+        write!(f, "Position policy: {}", self.position_policy)
+    }
+}
+
+/// Validates every line of `reader`, keeping only a bounded number of lines,
+/// each of bounded length, in memory at once.
+///
+/// Malformed lines are counted rather than treated as errors, so one bad
+/// line does not abort a multi-gigabyte run. Only I/O errors are returned.
+pub fn validate<R: BufRead>(reader: R, options: Options) -> io::Result<Tally> {
+    if options.threads <= 1 {
+        validate_serial(reader, options)
+    } else {
+        validate_parallel(reader, options)
+    }
+}
+
+/// Reads the next line into `line`, keeping at most `max` bytes of it.
+/// Returns `None` at the end of the input, otherwise whether the whole
+/// line fitted. The rest of a line that does not fit is read and dropped.
+fn read_line<R: BufRead>(
+    reader: &mut R,
+    line: &mut Vec<u8>,
+    max: usize,
+) -> io::Result<Option<bool>> {
+    let mut read = 0;
+    let mut fits = true;
+    loop {
+        let buf = match reader.fill_buf() {
+            Ok(buf) => buf,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        if buf.is_empty() {
+            break;
+        }
+        let (end, done) = match buf.iter().position(|&b| b == b'\n') {
+            Some(newline) => (newline + 1, true),
+            None => (buf.len(), false),
+        };
+        let room = max.saturating_sub(line.len());
+        if end > room {
+            fits = false;
+        }
+        if fits {
+            line.extend_from_slice(&buf[..end]);
+        }
+        read += end;
+        reader.consume(end);
+        if done {
+            break;
+        }
+    }
+    Ok(if read == 0 { None } else { Some(fits) })
+}
+
+fn validate_serial<R: BufRead>(mut reader: R, options: Options) -> io::Result<Tally> {
+    let mut tally = Tally::default();
+    let mut line = Vec::new();
+    while let Some(fits) = read_line(&mut reader, &mut line, options.max_line_bytes)? {
+        if fits {
+            tally.add_line(&line, options.indexing);
+        } else {
+            tally.malformed += 1;
+        }
+        line.clear();
+    }
+    Ok(tally)
+}
+
+fn validate_parallel<R: BufRead>(mut reader: R, options: Options) -> io::Result<Tally> {
+    let chunk_lines = options.chunk_lines.max(1);
+    // At most two chunks wait per worker, which bounds memory use.
+    let (sender, receiver) = sync_channel::<Vec<Vec<u8>>>(options.threads * 2);
+    let receiver = Mutex::new(receiver);
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..options.threads)
+            .map(|_| scope.spawn(|| worker(&receiver, options.indexing)))
+            .collect();
+
+        let mut read = Ok(());
+        // Lines too long to hand to a worker are counted here.
+        let mut tally = Tally::default();
+        let mut chunk = Vec::with_capacity(chunk_lines);
+        loop {
+            let mut line = Vec::new();
+            match read_line(&mut reader, &mut line, options.max_line_bytes) {
+                Ok(None) => break,
+                Ok(Some(true)) => chunk.push(line),
+                Ok(Some(false)) => tally.malformed += 1,
+                Err(e) => {
+                    read = Err(e);
+                    break;
+                }
+            }
+            if chunk.len() == chunk_lines {
+                let full = std::mem::replace(&mut chunk, Vec::with_capacity(chunk_lines));
+                sender.send(full).expect("Validation worker stopped early");
+            }
+        }
+        if !chunk.is_empty() {
+            sender.send(chunk).expect("Validation worker stopped early");
+        }
+        drop(sender);
+
+        for handle in workers {
+            tally += handle.join().expect("Validation worker panicked");
+        }
+        read.map(|_| tally)
+    })
+}
+
+fn worker(receiver: &Mutex<Receiver<Vec<Vec<u8>>>>, indexing: Indexing) -> Tally {
+    let mut tally = Tally::default();
+    loop {
+        let chunk = match receiver.lock().unwrap().recv() {
+            Ok(chunk) => chunk,
+            Err(_) => return tally,
+        };
+        for line in &chunk {
+            tally.add_line(line, indexing);
+        }
+    }
+}
+
+#[test]
+fn test_validate() {
+    let dump = "1-3 a: abcde\n1-3 b: cdefg\n\n2-9 c: ccccccccc\n3-1 a: aaa\r\n1-2 é: éa\n\
+                3-4 l: héllo\n";
+    let expected = Tally {
+        entries: 5,
+        malformed: 1,
+        count_policy: 3,
+        position_policy: 2,
+    };
+    assert_eq!(
+        validate(dump.as_bytes(), Options::default()).unwrap(),
+        expected
+    );
+
+    let with_bytes = Options {
+        indexing: Indexing::Bytes,
+        ..Options::default()
+    };
+    // Byte 3 of "héllo" is inside 'é', so only byte 4 holds an 'l'.
+    let tally = validate(dump.as_bytes(), with_bytes).unwrap();
+    assert_eq!(tally.position_policy, 3);
+
+    let invalid_utf8: &[u8] = b"1-3 a: ab\xffde\n1-3 a: abcde";
+    let tally = validate(invalid_utf8, Options::default()).unwrap();
+    assert_eq!((tally.entries, tally.malformed), (1, 1));
+}
+
+#[test]
+fn test_validate_parallel_matches_serial() {
+    let mut dump = String::new();
+    for i in 0..10_000 {
+        let low = i % 5 + 1;
+        let letter = ['a', 'b', 'ü'][i % 3];
+        dump.push_str(&format!(
+            "{}-{} {}: aübaüab{}\n",
+            low,
+            low + i % 4,
+            letter,
+            i
+        ));
+        if i % 997 == 0 {
+            dump.push_str("not an entry\n");
+        }
+    }
+
+    let serial = validate(dump.as_bytes(), Options::default()).unwrap();
+    assert_eq!(serial.entries + serial.malformed, 10_011);
+    for &threads in &[2, 3, 8] {
+        let options = Options {
+            threads,
+            chunk_lines: 100,
+            ..Options::default()
+        };
+        assert_eq!(validate(dump.as_bytes(), options).unwrap(), serial);
+    }
+}
+
+#[test]
+fn test_long_lines() {
+    let long = format!("1-3 a: {}\n", "a".repeat(100));
+    let dump = format!("1-3 a: abcde\n{}1-3 a: abcde\n{}", long, long.trim_end());
+    for &threads in &[1, 2] {
+        let options = Options {
+            threads,
+            max_line_bytes: 32,
+            ..Options::default()
+        };
+        let tally = validate(dump.as_bytes(), options).unwrap();
+        assert_eq!((tally.entries, tally.malformed), (2, 2));
+    }
+
+    // A line of exactly the limit, newline included, still fits.
+    let exact = Options {
+        max_line_bytes: 13,
+        ..Options::default()
+    };
+    let tally = validate("1-3 a: abcde\n".as_bytes(), exact).unwrap();
+    assert_eq!((tally.entries, tally.malformed), (1, 0));
+    let short = Options {
+        max_line_bytes: 12,
+        ..Options::default()
+    };
+    let tally = validate("1-3 a: abcde\n".as_bytes(), short).unwrap();
+    assert_eq!((tally.entries, tally.malformed), (0, 1));
+}
@@ -1,71 +1,56 @@
 use std::fs::File;
 use std::io::{self, BufRead};
 
-use itertools::Itertools;
+use day_2::{exactly_one_at, Indexing, PwdEntry};
 
-fn read_lines() -> Vec<String> {
+fn read_entries() -> Vec<PwdEntry> {
     let file = File::open("input_2.txt").expect("Unable to read file");
     io::BufReader::new(file)
         .lines()
-        .filter_map(Result::ok)
+        .map_while(Result::ok)
+        .enumerate()
+        .map(|(index, line)| {
+            line.parse()
+                .unwrap_or_else(|e| panic!("Line {}: {}", index + 1, e))
+        })
         .collect()
 }
 
-fn is_valid_password(input: &&String) -> bool {
-    let (policy, mut char_str, password) = input.split(" ").collect_tuple().unwrap();
+fn is_valid_password(entry: &&PwdEntry) -> bool {
+    let char_count = entry.pwd.chars().filter(|x| x == &entry.pat).count();
 
-    char_str = char_str.strip_suffix(':').unwrap();
-    let character: char = char_str.parse().unwrap();
-
-    let char_count = password.chars().filter(|x| x == &character).count();
-
-    let (min, max) = policy
-        .split("-")
-        .map(|x| x.parse::<usize>().unwrap())
-        .collect_tuple()
-        .unwrap();
-
-    min <= char_count && char_count <= max
+    entry.low <= char_count && char_count <= entry.high
 }
 
-fn part_1(lines: &Vec<String>) {
+fn part_1(entries: &[PwdEntry]) {
     println!("Part 1:");
 
-    let valid_count = lines.iter().filter(is_valid_password).count();
+    let valid_count = entries.iter().filter(is_valid_password).count();
     println!("Answer: {} passwords", valid_count);
 }
 
-fn is_valid_password_2(input: &&String) -> bool {
-    let (policy, mut char_str, password) = input.split(" ").collect_tuple().unwrap();
-    char_str = char_str.strip_suffix(':').unwrap();
-
-    let (first, second) = policy
-        .split("-")
-        .map(|x| x.parse::<usize>().unwrap())
-        .collect_tuple()
-        .unwrap();
-
-    let no_of_matches = password
-        .match_indices(char_str)
-        .map(|result| result.0 + 1)
-        .filter(|index| index == &first || index == &second)
-        .count();
-
-    no_of_matches == 1
+fn is_valid_password_2(entry: &&PwdEntry) -> bool {
+    exactly_one_at(
+        &entry.pwd,
+        entry.low,
+        entry.high,
+        entry.pat,
+        Indexing::Chars,
+    )
 }
 
-fn part_2(lines: &Vec<String>) {
+fn part_2(entries: &[PwdEntry]) {
     println!("Part 2:");
 
-    let valid_count = lines.iter().filter(is_valid_password_2).count();
+    let valid_count = entries.iter().filter(is_valid_password_2).count();
     println!("Answer: {} passwords", valid_count)
 }
 
 fn main() {
-    let lines = read_lines();
-    part_1(&lines);
+    let entries = read_entries();
+    part_1(&entries);
 
     println!("----------");
 
-    part_2(&lines);
+    part_2(&entries);
 }
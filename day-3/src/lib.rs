@@ -0,0 +1,232 @@
+// --- Day 3: Toboggan Trajectory--
+//
+// Pieces shared by the day 3 solutions.
+pub mod map;
+pub mod optimise;
+pub mod render;
+
+/// A straight run across the map, which repeats endlessly to each side.
+///
+/// The toboggan moves `right` columns (negative for leftwards) for every
+/// `down` rows (negative for upwards), so `right / down` may be any rational
+/// slope. A path ends once it leaves the top or bottom of the map, so an
+/// upward one wants a start row below the top. By default only the squares
+/// it lands on after each whole step are visited, like the puzzle;
+/// `crossing` instead visits every square the line passes through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trajectory {
+    pub right: isize,
+    pub down: isize,
+    /// `(row, col)` of the first square, which is always visited.
+    pub start: (usize, usize),
+    pub crossing: bool,
+}
+
+impl Trajectory {
+    /// Fails when `down` is zero: the map only ends at the top and the
+    /// bottom, so a path has to move up or down to ever finish.
+    pub fn new(right: isize, down: isize) -> Result<Trajectory, String> {
+        if down == 0 {
+            return Err(format!(
+                "a trajectory has to move up or down, but ({}, {}) does not",
+                right, down
+            ));
+        }
+        Ok(Trajectory {
+            right,
+            down,
+            start: (0, 0),
+            crossing: false,
+        })
+    }
+
+    pub fn starting_at(self, row: usize, col: usize) -> Trajectory {
+        Trajectory {
+            start: (row, col),
+            ..self
+        }
+    }
+
+    pub fn crossing(self) -> Trajectory {
+        Trajectory {
+            crossing: true,
+            ..self
+        }
+    }
+
+    /// The `(row, col)` squares visited on a map of the given size, with
+    /// `col` already wrapped into `0..width`.
+    pub fn squares(&self, height: usize, width: usize) -> Squares {
+        Squares {
+            steps: self.steps(height),
+            width: width as isize,
+        }
+    }
+
+    /// The `(row, col)` squares visited before leaving rows `0..height`,
+    /// with `col` counted across the repeated tiles: negative left of the
+    /// first tile, `width` or more right of it.
+    pub fn steps(&self, height: usize) -> Steps {
+        Steps {
+            path: *self,
+            height,
+            row: self.start.0 as isize,
+            col: self.start.1 as isize,
+            row_crossings: 0,
+            col_crossings: 0,
+        }
+    }
+}
+
+/// Iterator returned by `Trajectory::steps`.
+pub struct Steps {
+    path: Trajectory,
+    height: usize,
+    row: isize,
+    col: isize,
+    row_crossings: usize,
+    col_crossings: usize,
+}
+
+impl Iterator for Steps {
+    type Item = (usize, isize);
+
+    fn next(&mut self) -> Option<(usize, isize)> {
+        if self.row < 0 || self.row >= self.height as isize {
+            return None;
+        }
+        let square = (self.row as usize, self.col);
+
+        if !self.path.crossing {
+            self.row += self.path.down;
+            self.col += self.path.right;
+            return Some(square);
+        }
+
+        // Walk the line from the centre of the start square, stepping into
+        // whichever neighbour it reaches first (Amanatides & Woo). The k-th
+        // row boundary is met at (2k + 1) / (2 * down) and the k-th column
+        // boundary at (2k + 1) / (2 * |right|); cross-multiplying keeps it
+        // in integers. Passing exactly through a corner steps diagonally.
+        let (step_row, step_col) = (self.path.down.signum(), self.path.right.signum());
+        let (rows, cols) = (
+            self.path.down.unsigned_abs(),
+            self.path.right.unsigned_abs(),
+        );
+        if cols == 0 {
+            self.row += step_row;
+            return Some(square);
+        }
+        let next_row = (2 * self.row_crossings + 1) * cols;
+        let next_col = (2 * self.col_crossings + 1) * rows;
+        if next_row <= next_col {
+            self.row += step_row;
+            self.row_crossings += 1;
+        }
+        if next_col <= next_row {
+            self.col += step_col;
+            self.col_crossings += 1;
+        }
+        Some(square)
+    }
+}
+
+/// Iterator returned by `Trajectory::squares`.
+pub struct Squares {
+    steps: Steps,
+    width: isize,
+}
+
+impl Iterator for Squares {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        if self.width == 0 {
+            return None;
+        }
+        let (row, col) = self.steps.next()?;
+        Some((row, col.rem_euclid(self.width) as usize))
+    }
+}
+
+#[test]
+fn test_new() {
+    assert_eq!(
+        Trajectory::new(-3, 2).map(|x| (x.right, x.down)),
+        Ok((-3, 2))
+    );
+    assert_eq!(Trajectory::new(1, -2).map(|x| x.down), Ok(-2));
+    assert!(Trajectory::new(3, 0).is_err());
+}
+
+#[test]
+fn test_landing_squares() {
+    let squares: Vec<_> = Trajectory::new(3, 1).unwrap().squares(4, 5).collect();
+    assert_eq!(squares, vec![(0, 0), (1, 3), (2, 1), (3, 4)]);
+
+    let squares: Vec<_> = Trajectory::new(-2, 2)
+        .unwrap()
+        .starting_at(1, 1)
+        .squares(7, 5)
+        .collect();
+    assert_eq!(squares, vec![(1, 1), (3, 4), (5, 2)]);
+
+    // Upwards from the bottom row, stopping after row 0.
+    let squares: Vec<_> = Trajectory::new(3, -1)
+        .unwrap()
+        .starting_at(3, 0)
+        .squares(4, 5)
+        .collect();
+    assert_eq!(squares, vec![(3, 0), (2, 3), (1, 1), (0, 4)]);
+    let squares: Vec<_> = Trajectory::new(1, -2)
+        .unwrap()
+        .starting_at(4, 0)
+        .squares(5, 5)
+        .collect();
+    assert_eq!(squares, vec![(4, 0), (2, 1), (0, 2)]);
+    // From the top, an upward path leaves straight away.
+    let squares: Vec<_> = Trajectory::new(1, -1).unwrap().squares(4, 5).collect();
+    assert_eq!(squares, vec![(0, 0)]);
+}
+
+#[test]
+fn test_crossed_squares() {
+    // Half a column per row: each column change is its own square.
+    let squares: Vec<_> = Trajectory::new(1, 2)
+        .unwrap()
+        .crossing()
+        .squares(5, 10)
+        .collect();
+    assert_eq!(
+        squares,
+        vec![(0, 0), (1, 0), (1, 1), (2, 1), (3, 1), (3, 2), (4, 2)]
+    );
+
+    // Steep: every column between two landings is visited.
+    let squares: Vec<_> = Trajectory::new(-3, 1)
+        .unwrap()
+        .crossing()
+        .squares(2, 4)
+        .collect();
+    assert_eq!(squares, vec![(0, 0), (0, 3), (1, 2), (1, 1), (1, 0)]);
+
+    // A diagonal only touches corners, so it never leaves the diagonal.
+    let squares: Vec<_> = Trajectory::new(1, 1)
+        .unwrap()
+        .crossing()
+        .squares(3, 3)
+        .collect();
+    assert_eq!(squares, vec![(0, 0), (1, 1), (2, 2)]);
+
+    // Upwards mirrors downwards.
+    let squares: Vec<_> = Trajectory::new(1, -2)
+        .unwrap()
+        .starting_at(4, 0)
+        .crossing()
+        .squares(5, 10)
+        .collect();
+    assert_eq!(
+        squares,
+        vec![(4, 0), (3, 0), (3, 1), (2, 1), (1, 1), (1, 2), (0, 2)]
+    );
+}
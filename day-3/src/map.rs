@@ -0,0 +1,411 @@
+// Map representations for day 3, sharing the `Map` trait.
+//
+// Every backend is checked against the same maps and slopes by `conformance`
+// at the bottom of this file; add new ones there too.
+use crate::Trajectory;
+
+pub type MapLines = Vec<String>;
+
+pub trait Map {
+    fn new(lines: &MapLines) -> Self;
+    fn traverse(&self, forward: usize, down: usize) -> usize;
+
+    fn height(&self) -> usize;
+    fn width(&self) -> usize;
+    fn is_tree(&self, row: usize, col: usize) -> bool;
+
+    /// Like `traverse`, but for any `Trajectory`: leftward, from any start
+    /// square, or checking every square crossed.
+    fn traverse_path(&self, path: &Trajectory) -> usize {
+        path.squares(self.height(), self.width())
+            .filter(|&(row, col)| self.is_tree(row, col))
+            .count()
+    }
+}
+
+pub struct DefaultMap {
+    _map: Vec<Vec<char>>,
+}
+
+impl Map for DefaultMap {
+    fn new(lines: &MapLines) -> Self {
+        let vecs = lines.iter().map(|line| line.chars().collect::<Vec<char>>());
+
+        DefaultMap {
+            _map: vecs.collect(),
+        }
+    }
+
+    fn traverse(&self, forward: usize, down: usize) -> usize {
+        let vec_tuple = self._map.iter().step_by(down).enumerate();
+
+        vec_tuple
+            .filter(|x| {
+                let index = (forward * x.0) % x.1.len();
+                x.1[index] == '#'
+            })
+            .count()
+    }
+
+    fn height(&self) -> usize {
+        self._map.len()
+    }
+
+    fn width(&self) -> usize {
+        self._map.first().map_or(0, Vec::len)
+    }
+
+    fn is_tree(&self, row: usize, col: usize) -> bool {
+        self._map[row][col] == '#'
+    }
+}
+
+pub struct BoolMap {
+    _map: Vec<Vec<bool>>,
+}
+
+impl Map for BoolMap {
+    fn new(lines: &MapLines) -> Self {
+        let _map = lines
+            .iter()
+            .map(|line| line.chars().map(|character| character == '#').collect())
+            .collect();
+
+        BoolMap { _map }
+    }
+
+    fn traverse(&self, forward: usize, down: usize) -> usize {
+        let vec_tuple = self._map.iter().step_by(down).enumerate();
+
+        vec_tuple
+            .filter(|x| {
+                let index = (forward * x.0) % x.1.len();
+                x.1[index]
+            })
+            .count()
+    }
+
+    fn height(&self) -> usize {
+        self._map.len()
+    }
+
+    fn width(&self) -> usize {
+        self._map.first().map_or(0, Vec::len)
+    }
+
+    fn is_tree(&self, row: usize, col: usize) -> bool {
+        self._map[row][col]
+    }
+}
+
+/// One bit per square, packed into 64-bit words so rows of any width fit.
+pub struct BitMap {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    _map: Vec<u64>,
+}
+
+impl Map for BitMap {
+    fn new(lines: &MapLines) -> Self {
+        let width = lines.first().map_or(0, |line| line.chars().count());
+        let words_per_row = width.div_ceil(64);
+        let mut _map = vec![0u64; words_per_row * lines.len()];
+
+        for (row, line) in lines.iter().enumerate() {
+            let words = &mut _map[row * words_per_row..(row + 1) * words_per_row];
+            for (col, _) in line.chars().enumerate().filter(|x| x.1 == '#') {
+                words[col / 64] |= 1u64 << (col % 64);
+            }
+        }
+
+        BitMap {
+            width,
+            height: lines.len(),
+            words_per_row,
+            _map,
+        }
+    }
+
+    fn traverse(&self, forward: usize, down: usize) -> usize {
+        (0..self.height)
+            .step_by(down)
+            .enumerate()
+            .filter(|x| self.is_tree(x.1, (forward * x.0) % self.width))
+            .count()
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn is_tree(&self, row: usize, col: usize) -> bool {
+        let word = self._map[row * self.words_per_row + col / 64];
+        word & (1u64 << (col % 64)) != 0
+    }
+}
+
+/// matt's grid of squares.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridPoint {
+    OpenSquare,
+    Tree,
+}
+pub type GridLine = Vec<GridPoint>;
+
+pub fn parse_grid_line(line: &str) -> GridLine {
+    let mut grid_line = vec![];
+    for chr in line.chars() {
+        match chr {
+            '#' => grid_line.push(GridPoint::Tree),
+            '.' => grid_line.push(GridPoint::OpenSquare),
+            _ => panic!("Unexpected square {:?} in {:?}", chr, line),
+        }
+    }
+    grid_line
+}
+
+pub struct GridMap(pub Vec<GridLine>);
+
+impl Map for GridMap {
+    fn new(lines: &MapLines) -> Self {
+        GridMap(lines.iter().map(|line| parse_grid_line(line)).collect())
+    }
+
+    fn traverse(&self, forward: usize, down: usize) -> usize {
+        self.0
+            .iter()
+            .step_by(down)
+            .enumerate()
+            .filter(|x| x.1[(forward * x.0) % x.1.len()] == GridPoint::Tree)
+            .count()
+    }
+
+    fn height(&self) -> usize {
+        self.0.len()
+    }
+
+    fn width(&self) -> usize {
+        self.0.first().map_or(0, Vec::len)
+    }
+
+    fn is_tree(&self, row: usize, col: usize) -> bool {
+        self.0[row][col] == GridPoint::Tree
+    }
+}
+
+/// Only the columns holding trees, sorted, for each row. Small when trees
+/// are rare, and a square is a binary search away.
+pub struct SparseMap {
+    width: usize,
+    _trees: Vec<Vec<usize>>,
+}
+
+impl Map for SparseMap {
+    fn new(lines: &MapLines) -> Self {
+        let _trees = lines
+            .iter()
+            .map(|line| {
+                line.chars()
+                    .enumerate()
+                    .filter(|x| x.1 == '#')
+                    .map(|x| x.0)
+                    .collect()
+            })
+            .collect();
+
+        SparseMap {
+            width: lines.first().map_or(0, |line| line.chars().count()),
+            _trees,
+        }
+    }
+
+    fn traverse(&self, forward: usize, down: usize) -> usize {
+        self._trees
+            .iter()
+            .step_by(down)
+            .enumerate()
+            .filter(|x| x.1.binary_search(&((forward * x.0) % self.width)).is_ok())
+            .count()
+    }
+
+    fn height(&self) -> usize {
+        self._trees.len()
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn is_tree(&self, row: usize, col: usize) -> bool {
+        self._trees[row].binary_search(&col).is_ok()
+    }
+}
+
+#[cfg(test)]
+fn random_lines(width: usize, height: usize) -> MapLines {
+    // A small LCG keeps the maps reproducible without extra dependencies.
+    let mut state: u64 = 0x2020_1203;
+    (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    if (state >> 33) & 3 == 0 {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The map from the puzzle statement.
+#[cfg(test)]
+pub(crate) fn example_lines() -> MapLines {
+    vec![
+        "..##.......",
+        "#...#...#..",
+        ".#....#..#.",
+        "..#.#...#.#",
+        ".#...##..#.",
+        "..#.##.....",
+        ".#.#.#....#",
+        ".#........#",
+        "#.##...#...",
+        "#...##....#",
+        ".#..#...#.#",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+/// The shared suite every `Map` backend has to pass, checked against the
+/// map text itself.
+#[cfg(test)]
+fn conformance<M: Map>() {
+    let grid = |lines: &MapLines| -> Vec<Vec<bool>> {
+        lines
+            .iter()
+            .map(|line| line.chars().map(|c| c == '#').collect())
+            .collect()
+    };
+    let reference = |trees: &Vec<Vec<bool>>, path: &Trajectory| {
+        path.squares(trees.len(), trees[0].len())
+            .filter(|&(row, col)| trees[row][col])
+            .count()
+    };
+
+    // Known answers from the puzzle example.
+    let example = M::new(&example_lines());
+    let known = [
+        (Trajectory::new(1, 1).unwrap(), 2),
+        (Trajectory::new(3, 1).unwrap(), 7),
+        (Trajectory::new(5, 1).unwrap(), 3),
+        (Trajectory::new(7, 1).unwrap(), 4),
+        (Trajectory::new(1, 2).unwrap(), 2),
+        (Trajectory::new(-3, 1).unwrap(), 3),
+        (Trajectory::new(-1, 1).unwrap().starting_at(2, 9), 5),
+        (Trajectory::new(3, 2).unwrap().crossing(), 8),
+        (Trajectory::new(3, -1).unwrap().starting_at(10, 0), 3),
+    ];
+    for (path, trees) in known.iter() {
+        assert_eq!(example.traverse_path(path), *trees, "{:?}", path);
+    }
+
+    let slopes = [
+        (1, 1),
+        (3, 1),
+        (5, 1),
+        (7, 1),
+        (1, 2),
+        (31, 1),
+        (63, 1),
+        (64, 3),
+    ];
+    let mut maps = vec![example_lines()];
+    for &width in &[1, 31, 32, 63, 64, 65, 1000] {
+        maps.push(random_lines(width, 323));
+    }
+    for lines in &maps {
+        let map = M::new(lines);
+        let trees = grid(lines);
+        assert_eq!((map.height(), map.width()), (trees.len(), trees[0].len()));
+        for (row, line) in trees.iter().enumerate() {
+            for (col, &tree) in line.iter().enumerate() {
+                assert_eq!(map.is_tree(row, col), tree, "square ({}, {})", row, col);
+            }
+        }
+
+        for &(forward, down) in &slopes {
+            let path = Trajectory::new(forward as isize, down as isize).unwrap();
+            let expected = reference(&trees, &path);
+            assert_eq!(
+                map.traverse(forward, down),
+                expected,
+                "width {} slope ({}, {})",
+                map.width(),
+                forward,
+                down
+            );
+            for path in &[
+                path,
+                path.crossing(),
+                Trajectory::new(-(forward as isize), down as isize)
+                    .unwrap()
+                    .starting_at(5, 3),
+                Trajectory::new(forward as isize, -(down as isize))
+                    .unwrap()
+                    .starting_at(map.height() - 1, 7),
+                Trajectory::new(forward as isize, -(down as isize))
+                    .unwrap()
+                    .starting_at(map.height() - 1, 7)
+                    .crossing(),
+            ] {
+                assert_eq!(map.traverse_path(path), reference(&trees, path));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_default_map() {
+    conformance::<DefaultMap>();
+}
+
+#[test]
+fn test_bool_map() {
+    conformance::<BoolMap>();
+}
+
+#[test]
+fn test_bit_map() {
+    conformance::<BitMap>();
+}
+
+#[test]
+fn test_grid_map() {
+    conformance::<GridMap>();
+}
+
+#[test]
+fn test_sparse_map() {
+    conformance::<SparseMap>();
+}
+
+#[test]
+fn test_bit_map_last_column() {
+    let lines: MapLines = vec![".".repeat(63) + "#", ".".repeat(63) + "#"];
+    assert_eq!(BitMap::new(&lines).traverse(63, 1), 1);
+    let lines: MapLines = vec!["#".to_string() + &".".repeat(999); 3];
+    assert_eq!(BitMap::new(&lines).traverse(500, 1), 2);
+}
@@ -5,63 +5,42 @@
 use std::fs::File;
 use std::io::{self, BufRead};
 
-#[derive(Debug, Clone)]
-enum GridPoint {
-    OpenSquare,
-    Tree,
-}
-type GridLine = Vec<GridPoint>;
-type GridMap = Vec<GridLine>;
-struct Slope {
-    right: usize,
-    down: usize,
-}
-impl Slope {
-    fn new(right: usize, down: usize) -> Slope {
-        Slope { right, down }
-    }
+use day_3::map::{parse_grid_line, GridMap, Map};
+use day_3::Trajectory;
+
+/// A path from the top-left, landing after each whole step like the puzzle.
+fn slope(right: isize, down: isize) -> Trajectory {
+    Trajectory::new(right, down).expect("slopes move up or down")
 }
 
-fn trees_encountered(map: &GridMap, slope: &Slope) -> usize {
-    let mut tree_count = 0;
-    let mut right_offset = 0;
-    let mut down_offset = 0;
-    for _ in 0..(map.len() - 1) {
-        right_offset += slope.right;
-        down_offset += slope.down;
-        if down_offset > map.len() - 1 {
-            break;
-        }
-        match map[down_offset][right_offset % map[0].len()] {
-            GridPoint::Tree => tree_count += 1,
-            _ => {}
-        }
-    }
-    tree_count
+/// Trees hit after leaving the start square; the start square itself
+/// never counts.
+fn trees_encountered(map: &GridMap, path: &Trajectory) -> usize {
+    path.squares(map.height(), map.width())
+        .skip(1)
+        .filter(|&(row, col)| map.is_tree(row, col))
+        .count()
 }
 
-fn trees_encountered_multiplied(map: &GridMap, slopes: Vec<Slope>) -> usize {
-    slopes
-        .iter()
-        .map(|s| trees_encountered(map, s))
-        .fold(1, |acc, x| acc * x)
+fn trees_encountered_multiplied(map: &GridMap, paths: Vec<Trajectory>) -> usize {
+    paths.iter().map(|x| trees_encountered(map, x)).product()
 }
 
 fn main() {
     let map = input().unwrap();
-    let slope = Slope::new(3, 1);
+    let path = slope(3, 1);
 
-    println!("Part One: {}", trees_encountered(&map, &slope));
+    println!("Part One: {}", trees_encountered(&map, &path));
     println!(
         "Part Two: {}",
         trees_encountered_multiplied(
             &map,
             vec!(
-                Slope::new(1, 1),
-                Slope::new(3, 1),
-                Slope::new(5, 1),
-                Slope::new(7, 1),
-                Slope::new(1, 2),
+                slope(1, 1),
+                slope(3, 1),
+                slope(5, 1),
+                slope(7, 1),
+                slope(1, 2),
             )
         )
     );
@@ -73,21 +52,9 @@ fn input() -> io::Result<GridMap> {
     let buf = io::BufReader::new(file);
     for item in buf.lines() {
         let line = item?;
-        input.push(parse_input_line(&line))
+        input.push(parse_grid_line(&line))
     }
-    Ok(input)
-}
-
-fn parse_input_line(line: &str) -> GridLine {
-    let mut grid_line = vec![];
-    for chr in line.chars() {
-        match chr {
-            '#' => grid_line.push(GridPoint::Tree),
-            '.' => grid_line.push(GridPoint::OpenSquare),
-            _ => unreachable!(),
-        }
-    }
-    grid_line
+    Ok(GridMap(input))
 }
 
 #[test]
@@ -105,23 +72,48 @@ fn test_matt() {
         "#...##....#",
         ".#..#...#.#",
     ];
-    let map: Vec<_> = map_lines_str
-        .into_iter()
-        .map(|l| parse_input_line(l))
-        .collect();
-    let slope = Slope::new(3, 1);
-    assert_eq!(trees_encountered(&map, &slope), 7);
+    let map = GridMap(map_lines_str.into_iter().map(parse_grid_line).collect());
+    let path = slope(3, 1);
+    assert_eq!(trees_encountered(&map, &path), 7);
     assert_eq!(
         trees_encountered_multiplied(
             &map,
             vec!(
-                Slope::new(1, 1),
-                Slope::new(3, 1),
-                Slope::new(5, 1),
-                Slope::new(7, 1),
-                Slope::new(1, 2),
+                slope(1, 1),
+                slope(3, 1),
+                slope(5, 1),
+                slope(7, 1),
+                slope(1, 2),
             )
         ),
         336
     );
 }
+
+#[test]
+fn test_trajectories() {
+    let map = GridMap(
+        vec!["#..", ".#.", "..#", "#.#"]
+            .into_iter()
+            .map(parse_grid_line)
+            .collect(),
+    );
+    assert_eq!(map.traverse_path(&Trajectory::new(1, 1).unwrap()), 4);
+    assert_eq!(map.traverse_path(&Trajectory::new(-1, 1).unwrap()), 2);
+    assert_eq!(
+        map.traverse_path(&Trajectory::new(0, 2).unwrap().starting_at(0, 2)),
+        1
+    );
+    assert_eq!(
+        map.traverse_path(&Trajectory::new(1, 2).unwrap().crossing()),
+        3
+    );
+
+    // Unlike `traverse_path`, the tree a path starts on is left out.
+    assert_eq!(trees_encountered(&map, &slope(1, 1)), 3);
+    assert_eq!(trees_encountered(&map, &slope(3, 1)), 1);
+    assert_eq!(trees_encountered(&map, &slope(-1, 1)), 1);
+    assert_eq!(trees_encountered(&map, &slope(0, 2).starting_at(0, 2)), 1);
+    assert_eq!(trees_encountered(&map, &slope(1, 2).crossing()), 2);
+    assert_eq!(trees_encountered(&map, &slope(1, -1).starting_at(3, 0)), 1);
+}
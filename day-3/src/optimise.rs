@@ -0,0 +1,159 @@
+// Searching a range of slopes for the best route down the map.
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
+
+use crate::map::Map;
+use crate::Trajectory;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    FewestTrees,
+    MostTrees,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candidate {
+    pub right: isize,
+    pub down: usize,
+    pub trees: usize,
+}
+
+impl Candidate {
+    pub fn trajectory(&self) -> Result<Trajectory, String> {
+        Trajectory::new(self.right, self.down as isize)
+    }
+
+    /// Best first for `goal`; ties go to the gentler slope.
+    fn rank(&self, other: &Candidate, goal: Goal) -> Ordering {
+        let trees = match goal {
+            Goal::FewestTrees => self.trees.cmp(&other.trees),
+            Goal::MostTrees => other.trees.cmp(&self.trees),
+        };
+        trees
+            .then(other.down.cmp(&self.down))
+            .then(self.right.abs().cmp(&other.right.abs()))
+            .then(self.right.cmp(&other.right))
+    }
+}
+
+/// The `top` best slopes with `right` and `down` steps in the given ranges,
+/// best first.
+///
+/// Candidates are counted square by square and dropped as soon as they can
+/// no longer beat the worst slope kept so far, so most of a large search
+/// never walks a whole path. A `BitMap` makes each square a single bit test.
+pub fn rank_slopes<M: Map>(
+    map: &M,
+    rights: RangeInclusive<isize>,
+    downs: RangeInclusive<usize>,
+    goal: Goal,
+    top: usize,
+) -> Vec<Candidate> {
+    let mut ranking: Vec<Candidate> = Vec::with_capacity(top + 1);
+    if top == 0 {
+        return ranking;
+    }
+
+    for down in downs.filter(|&down| down > 0) {
+        let landings = map.height().div_ceil(down);
+        for right in rights.clone() {
+            let bound = if ranking.len() == top {
+                ranking.last().map(|worst| worst.trees)
+            } else {
+                None
+            };
+            let trees = match count_within(map, right, down, landings, goal, bound) {
+                Some(trees) => trees,
+                None => continue,
+            };
+
+            let candidate = Candidate { right, down, trees };
+            let at = ranking
+                .binary_search_by(|kept| kept.rank(&candidate, goal))
+                .unwrap_or_else(|at| at);
+            if at < top {
+                ranking.insert(at, candidate);
+                ranking.truncate(top);
+            }
+        }
+    }
+    ranking
+}
+
+/// The single best slope, if the ranges hold any.
+pub fn best_slope<M: Map>(
+    map: &M,
+    rights: RangeInclusive<isize>,
+    downs: RangeInclusive<usize>,
+    goal: Goal,
+) -> Option<Candidate> {
+    rank_slopes(map, rights, downs, goal, 1).pop()
+}
+
+/// Counts the trees on a path, giving up with `None` once the count is
+/// certain to be worse than `bound`.
+fn count_within<M: Map>(
+    map: &M,
+    right: isize,
+    down: usize,
+    landings: usize,
+    goal: Goal,
+    bound: Option<usize>,
+) -> Option<usize> {
+    let squares = Trajectory::new(right, down as isize)
+        .expect("rank_slopes skips down of zero")
+        .squares(map.height(), map.width());
+    let mut trees = 0;
+    for (visited, (row, col)) in squares.enumerate() {
+        if map.is_tree(row, col) {
+            trees += 1;
+        }
+        match (goal, bound) {
+            (Goal::FewestTrees, Some(bound)) if trees > bound => return None,
+            (Goal::MostTrees, Some(bound)) if trees + (landings - visited - 1) < bound => {
+                return None
+            }
+            _ => (),
+        }
+    }
+    Some(trees)
+}
+
+#[cfg(test)]
+fn example() -> crate::map::BitMap {
+    crate::map::BitMap::new(&crate::map::example_lines())
+}
+
+#[test]
+fn test_rank_slopes() {
+    let map = example();
+
+    // Exhaustively score the part two ranges to check the pruned search.
+    let mut all: Vec<Candidate> = (1..=2)
+        .flat_map(|down| (-7..=7).map(move |right| (right, down)))
+        .map(|(right, down)| Candidate {
+            right,
+            down,
+            trees: map.traverse_path(&Trajectory::new(right, down as isize).unwrap()),
+        })
+        .collect();
+
+    for &goal in &[Goal::FewestTrees, Goal::MostTrees] {
+        all.sort_by(|a, b| a.rank(b, goal));
+        for &top in &[1, 3, 10] {
+            let ranking = rank_slopes(&map, -7..=7, 1..=2, goal, top);
+            assert_eq!(ranking, all[..top].to_vec(), "{:?} top {}", goal, top);
+        }
+    }
+}
+
+#[test]
+fn test_best_slope() {
+    let map = example();
+    let best = best_slope(&map, 1..=7, 1..=1, Goal::FewestTrees).unwrap();
+    assert_eq!((best.right, best.down, best.trees), (2, 1, 1));
+    let worst = best_slope(&map, 1..=7, 1..=1, Goal::MostTrees).unwrap();
+    assert_eq!((worst.right, worst.down, worst.trees), (3, 1, 7));
+    assert!(best_slope(&map, 1..=7, 0..=0, Goal::FewestTrees).is_none());
+    assert!(rank_slopes(&map, 1..=7, 1..=2, Goal::FewestTrees, 0).is_empty());
+}
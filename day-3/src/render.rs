@@ -0,0 +1,205 @@
+// Drawing the map with toboggan paths on top, like the puzzle statement.
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::ops::Range;
+
+use crate::map::Map;
+use crate::Trajectory;
+
+const ANSI_COLOURS: [u8; 6] = [31, 32, 33, 34, 35, 36];
+const SVG_COLOURS: [&str; 6] = [
+    "#d62728", "#1f77b4", "#ff7f0e", "#9467bd", "#e377c2", "#17becf",
+];
+const SVG_CELL: isize = 10;
+
+/// A map with any number of paths drawn over it.
+///
+/// The map is repeated sideways as often as the paths need, and every
+/// visited square is marked `X` on a tree or `O` on open snow. When paths
+/// share a square, the one added first is drawn.
+pub struct Overlay<'a, M> {
+    map: &'a M,
+    paths: Vec<Trajectory>,
+    rows: usize,
+}
+
+impl<'a, M: Map> Overlay<'a, M> {
+    pub fn new(map: &'a M) -> Self {
+        Overlay {
+            map,
+            paths: Vec::new(),
+            rows: map.height(),
+        }
+    }
+
+    pub fn path(mut self, path: Trajectory) -> Self {
+        self.paths.push(path);
+        self
+    }
+
+    /// Only draws the first `rows` rows, handy for a terminal.
+    pub fn rows(mut self, rows: usize) -> Self {
+        self.rows = rows.min(self.map.height());
+        self
+    }
+
+    /// Which path, by index, marks each `(row, col)`, with `col` counted
+    /// across the repeated tiles.
+    fn marks(&self) -> HashMap<(usize, isize), usize> {
+        let mut marks = HashMap::new();
+        for (index, path) in self.paths.iter().enumerate() {
+            for square in path.steps(self.rows) {
+                marks.entry(square).or_insert(index);
+            }
+        }
+        marks
+    }
+
+    /// Whole tiles covering the first tile and every mark.
+    fn columns(&self, marks: &HashMap<(usize, isize), usize>) -> Range<isize> {
+        let width = self.map.width() as isize;
+        if width == 0 {
+            return 0..0;
+        }
+        let low = marks.keys().map(|m| m.1).min().unwrap_or(0).min(0);
+        let high = marks.keys().map(|m| m.1).max().unwrap_or(0).max(0);
+        low.div_euclid(width) * width..(high.div_euclid(width) + 1) * width
+    }
+
+    /// Plain text, optionally with ANSI colours telling the paths apart.
+    pub fn to_text(&self, colour: bool) -> String {
+        let marks = self.marks();
+        let columns = self.columns(&marks);
+        let width = self.map.width() as isize;
+
+        let mut text = String::new();
+        for row in 0..self.rows {
+            for col in columns.clone() {
+                let tree = self.map.is_tree(row, col.rem_euclid(width) as usize);
+                match marks.get(&(row, col)) {
+                    Some(&index) => {
+                        let mark = if tree { 'X' } else { 'O' };
+                        if colour {
+                            let code = ANSI_COLOURS[index % ANSI_COLOURS.len()];
+                            let _ = write!(text, "\x1b[1;{}m{}\x1b[0m", code, mark);
+                        } else {
+                            text.push(mark);
+                        }
+                    }
+                    None => text.push(if tree { '#' } else { '.' }),
+                }
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// A standalone SVG image, one colour per path.
+    pub fn to_svg(&self) -> String {
+        let marks = self.marks();
+        let columns = self.columns(&marks);
+        let width = self.map.width() as isize;
+        let (cols, rows) = (columns.len() as isize, self.rows as isize);
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}">"#,
+            cols * SVG_CELL,
+            rows * SVG_CELL
+        );
+        let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+        for row in 0..rows {
+            for col in columns.clone() {
+                let (x, y) = ((col - columns.start) * SVG_CELL, row * SVG_CELL);
+                let tree = self
+                    .map
+                    .is_tree(row as usize, col.rem_euclid(width) as usize);
+                if tree {
+                    let _ = writeln!(
+                        svg,
+                        r##"<rect x="{}" y="{}" width="{}" height="{}" fill="#2e7d32"/>"##,
+                        x, y, SVG_CELL, SVG_CELL
+                    );
+                }
+                let index = match marks.get(&(row as usize, col)) {
+                    Some(&index) => index,
+                    None => continue,
+                };
+                let stroke = SVG_COLOURS[index % SVG_COLOURS.len()];
+                if tree {
+                    let (x1, y1, x2, y2) = (x + 2, y + 2, x + SVG_CELL - 2, y + SVG_CELL - 2);
+                    let _ = writeln!(
+                        svg,
+                        r#"<path d="M{} {}L{} {}M{} {}L{} {}" stroke="{}" stroke-width="2"/>"#,
+                        x1, y1, x2, y2, x1, y2, x2, y1, stroke
+                    );
+                } else {
+                    let _ = writeln!(
+                        svg,
+                        r#"<circle cx="{}" cy="{}" r="{}" fill="none" stroke="{}" stroke-width="2"/>"#,
+                        x + SVG_CELL / 2,
+                        y + SVG_CELL / 2,
+                        SVG_CELL / 2 - 2,
+                        stroke
+                    );
+                }
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// The first five rows of the puzzle's map.
+#[cfg(test)]
+fn example() -> crate::map::BoolMap {
+    let lines: crate::map::MapLines = crate::map::example_lines().into_iter().take(5).collect();
+    crate::map::BoolMap::new(&lines)
+}
+
+#[test]
+fn test_text() {
+    let map = example();
+    let text = Overlay::new(&map)
+        .path(Trajectory::new(3, 1).unwrap())
+        .to_text(false);
+    assert_eq!(
+        text,
+        "O.##.........##.......\n\
+         #..O#...#..#...#...#..\n\
+         .#....X..#..#....#..#.\n\
+         ..#.#...#O#..#.#...#.#\n\
+         .#...##..#..X...##..#.\n"
+    );
+
+    let text = Overlay::new(&map)
+        .path(Trajectory::new(-1, 2).unwrap())
+        .path(Trajectory::new(1, 1).unwrap())
+        .rows(3)
+        .to_text(true);
+    assert_eq!(
+        text,
+        "..##.......\x1b[1;31mO\x1b[0m.##.......\n\
+         #...#...#..#\x1b[1;32mO\x1b[0m..#...#..\n\
+         .#....#..#\x1b[1;31mO\x1b[0m.#\x1b[1;32mO\x1b[0m...#..#.\n"
+    );
+}
+
+#[test]
+fn test_svg() {
+    let map = example();
+    let svg = Overlay::new(&map)
+        .path(Trajectory::new(3, 1).unwrap())
+        .path(Trajectory::new(1, 1).unwrap())
+        .to_svg();
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="220" height="50">"#));
+    assert!(svg.ends_with("</svg>\n"));
+    // Both paths share the start square, which takes the first colour.
+    assert_eq!(svg.matches("#d62728").count(), 5);
+    assert_eq!(svg.matches("#1f77b4").count(), 4);
+    assert_eq!(
+        svg.matches("<circle").count() + svg.matches("<path").count(),
+        9
+    );
+}
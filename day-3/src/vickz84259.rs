@@ -2,124 +2,16 @@ use std::fs::File;
 use std::io::{self, BufRead};
 use std::time::Instant;
 
-type MapLines = Vec<String>;
+use day_3::map::{BitMap, BoolMap, DefaultMap, GridMap, Map, MapLines, SparseMap};
+use day_3::optimise::{rank_slopes, Goal};
+use day_3::render::Overlay;
+use day_3::Trajectory;
 
 fn get_lines() -> MapLines {
     let file = File::open("input_3.txt").expect("Unable to read file");
     let lines = io::BufReader::new(file).lines();
 
-    lines.filter_map(Result::ok).collect()
-}
-
-trait Map {
-    fn new(lines: &MapLines) -> Self;
-    fn traverse(&self, forward: usize, down: usize) -> usize;
-}
-
-struct DefaultMap {
-    _map: Vec<Vec<char>>,
-}
-
-impl Map for DefaultMap {
-    fn new(lines: &MapLines) -> Self {
-        let vecs = lines.iter().map(|line| line.chars().collect::<Vec<char>>());
-
-        DefaultMap {
-            _map: vecs.collect(),
-        }
-    }
-
-    fn traverse(&self, forward: usize, down: usize) -> usize {
-        let vec_tuple = self._map.iter().step_by(down).enumerate();
-
-        vec_tuple
-            .filter(|x| {
-                let index = (forward * x.0) % x.1.len();
-                match x.1[index] {
-                    '#' => true,
-                    _ => false,
-                }
-            })
-            .count()
-    }
-}
-
-struct BoolMap {
-    _map: Vec<Vec<bool>>,
-}
-
-impl Map for BoolMap {
-    fn new(lines: &MapLines) -> Self {
-        let _map = lines
-            .iter()
-            .map(|line| {
-                line.chars()
-                    .map(|character| match character {
-                        '#' => true,
-                        _ => false,
-                    })
-                    .collect()
-            })
-            .collect();
-
-        BoolMap { _map }
-    }
-
-    fn traverse(&self, forward: usize, down: usize) -> usize {
-        let vec_tuple = self._map.iter().step_by(down).enumerate();
-
-        vec_tuple
-            .filter(|x| {
-                let index = (forward * x.0) % x.1.len();
-                x.1[index]
-            })
-            .count()
-    }
-}
-
-struct BitMap {
-    width: u32,
-    _map: Vec<u32>,
-}
-
-impl Map for BitMap {
-    fn new(lines: &MapLines) -> Self {
-        let mut width: u32 = 0;
-
-        let _map = lines
-            .iter()
-            .map(|line| {
-                let mut row: u32 = 0;
-                width = line.len() as u32;
-
-                line.chars().enumerate().for_each(|x| {
-                    match x.1 {
-                        '#' => row = row | (1u32 << (width - (x.0 as u32))),
-                        _ => (),
-                    };
-                });
-                row
-            })
-            .collect();
-
-        BitMap { width, _map }
-    }
-
-    fn traverse(&self, forward: usize, down: usize) -> usize {
-        let vec_tuple = self._map.iter().step_by(down).enumerate();
-
-        vec_tuple
-            .filter(|x| {
-                let index: u32 = ((forward * x.0) as u32) % self.width;
-                let bit = x.1 & (1u32 << (self.width - index));
-
-                match bit {
-                    0 => false,
-                    _ => true,
-                }
-            })
-            .count()
-    }
+    lines.map_while(Result::ok).collect()
 }
 
 fn part_1<T: Map>(map: &T) {
@@ -129,53 +21,94 @@ fn part_1<T: Map>(map: &T) {
 }
 
 fn part_2<T: Map>(map: &T) {
-    let paths = vec![(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+    let paths = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
 
     let answer: usize = paths.iter().map(|x| map.traverse(x.0, x.1)).product();
     println!("Answer: {}", answer);
 }
 
-fn main() {
-    let lines = get_lines();
+fn best_slopes<T: Map>(map: &T) {
+    for candidate in rank_slopes(map, -10..=10, 1..=3, Goal::FewestTrees, 3) {
+        println!(
+            "\tRight {}, down {}: {} trees",
+            candidate.right, candidate.down, candidate.trees
+        );
+    }
+}
 
-    println!("Part 1: \n ----------");
+/// Draws the part 2 slopes, to the terminal or to an SVG file.
+fn render<T: Map>(map: &T, svg: Option<String>) {
+    let overlay = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
+        .iter()
+        .fold(Overlay::new(map), |overlay, x| {
+            overlay.path(Trajectory::new(x.0, x.1).unwrap())
+        });
+
+    match svg {
+        Some(path) => std::fs::write(path, overlay.to_svg()).expect("Unable to write file"),
+        None => print!("{}", overlay.rows(40).to_text(true)),
+    }
+}
 
-    println!("Default Map");
-    let mut start = Instant::now();
+const BACKENDS: [&str; 5] = ["default", "bool", "bit", "grid", "sparse"];
 
-    let default_map = DefaultMap::new(&lines);
-    part_1(&default_map);
-    println!("\tTime Taken: {:?}", start.elapsed());
+fn benchmark<T: Map>(name: &str, lines: &MapLines) {
+    println!("{} Map \n----------", name);
 
-    println!("Bool Map");
-    start = Instant::now();
+    let mut start = Instant::now();
+    let map = T::new(lines);
+    println!("\tBuilt in: {:?}", start.elapsed());
 
-    let bool_map = BoolMap::new(&lines);
-    part_1(&bool_map);
+    println!("Part 1:");
+    start = Instant::now();
+    part_1(&map);
     println!("\tTime Taken: {:?}", start.elapsed());
 
-    println!("Bit Map");
-
+    println!("Part 2:");
     start = Instant::now();
-
-    let bit_map = BitMap::new(&lines);
-    part_1(&bit_map);
+    part_2(&map);
     println!("\tTime Taken: {:?}", start.elapsed());
+}
 
-    println!("---------- \nPart 2: \n----------");
+/// Runs the benchmark for a backend named in `BACKENDS`.
+fn benchmark_by_name(name: &str, lines: &MapLines) -> bool {
+    match name {
+        "default" => benchmark::<DefaultMap>("Default", lines),
+        "bool" => benchmark::<BoolMap>("Bool", lines),
+        "bit" => benchmark::<BitMap>("Bit", lines),
+        "grid" => benchmark::<GridMap>("Grid", lines),
+        "sparse" => benchmark::<SparseMap>("Sparse", lines),
+        _ => return false,
+    }
+    true
+}
 
-    println!("Default Map");
-    start = Instant::now();
-    part_2(&default_map);
-    println!("\tTime Taken: {:?}", start.elapsed());
+fn main() {
+    let lines = get_lines();
 
-    println!("Bool Map");
-    start = Instant::now();
-    part_2(&bool_map);
-    println!("\tTime Taken: {:?}", start.elapsed());
+    // `vickz84259 render [file.svg]` draws the slopes, `vickz84259 bit grid`
+    // only benchmarks the named backends.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("render") {
+        render(&BitMap::new(&lines), args.get(1).cloned());
+        return;
+    }
 
-    println!("Bit Map");
-    start = Instant::now();
-    part_2(&bit_map);
+    let names: Vec<&str> = if args.is_empty() {
+        BACKENDS.to_vec()
+    } else {
+        args.iter().map(String::as_str).collect()
+    };
+    for name in names {
+        if !benchmark_by_name(name, &lines) {
+            eprintln!("Unknown map {:?}, expected one of {:?}", name, BACKENDS);
+            return;
+        }
+        println!("----------");
+    }
+
+    println!("Best slopes: \n----------");
+    let start = Instant::now();
+    best_slopes(&BitMap::new(&lines));
     println!("\tTime Taken: {:?}", start.elapsed());
 }
@@ -0,0 +1,254 @@
+// Writing a batch of records back out, for cleaning up files or other tools.
+use std::fmt::Write;
+use std::str::FromStr;
+
+use crate::parse::RawRecord;
+use crate::passport::ValidatedPassport;
+use crate::rules::Rules;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Blank-line-separated `key:value` records, like the puzzle input.
+    Batch,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "batch" => Ok(Format::Batch),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!(
+                "unknown format {:?}, expected batch, json or csv",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    All,
+    Valid,
+    Invalid,
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Filter, String> {
+        match s {
+            "all" => Ok(Filter::All),
+            "valid" => Ok(Filter::Valid),
+            "invalid" => Ok(Filter::Invalid),
+            _ => Err(format!(
+                "unknown filter {:?}, expected all, valid or invalid",
+                s
+            )),
+        }
+    }
+}
+
+/// Writes records with their fields in canonical order: those the rules
+/// know in rules order, then any others in the order they were given.
+pub struct Export<'a> {
+    rules: &'a Rules,
+    filter: Filter,
+}
+
+impl<'a> Export<'a> {
+    pub fn new(rules: &'a Rules) -> Self {
+        Export {
+            rules,
+            filter: Filter::All,
+        }
+    }
+
+    /// Only writes records that are, or are not, valid: the rules accept
+    /// them and they convert to a `ValidatedPassport`, as Part Two counts.
+    pub fn only(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn render(&self, format: Format, records: &[RawRecord]) -> String {
+        match format {
+            Format::Batch => self.to_batch(records),
+            Format::Json => self.to_json(records),
+            Format::Csv => self.to_csv(records),
+        }
+    }
+
+    fn selected<'r>(&self, records: &'r [RawRecord]) -> Vec<&'r RawRecord> {
+        let valid = |record: &RawRecord| ValidatedPassport::checked(self.rules, record).is_ok();
+        records
+            .iter()
+            .filter(|record| match self.filter {
+                Filter::All => true,
+                Filter::Valid => valid(record),
+                Filter::Invalid => !valid(record),
+            })
+            .collect()
+    }
+
+    fn ordered<'r>(&self, record: &'r RawRecord) -> Vec<(&'r str, &'r str)> {
+        let known = self.rules.fields().filter_map(|rule| {
+            record
+                .fields
+                .iter()
+                .find(|x| x.key == rule.field)
+                .map(|x| (x.key.as_str(), x.value.as_str()))
+        });
+        let unknown = record.pairs().filter(|x| self.rules.get(x.0).is_none());
+        known.chain(unknown).collect()
+    }
+
+    /// One record per paragraph, all its fields on a single line.
+    pub fn to_batch(&self, records: &[RawRecord]) -> String {
+        let records: Vec<String> = self
+            .selected(records)
+            .into_iter()
+            .map(|record| {
+                let fields: Vec<String> = self
+                    .ordered(record)
+                    .iter()
+                    .map(|(key, value)| format!("{}:{}", key, value))
+                    .collect();
+                fields.join(" ") + "\n"
+            })
+            .collect();
+        records.join("\n")
+    }
+
+    /// An array of objects, every value a string.
+    pub fn to_json(&self, records: &[RawRecord]) -> String {
+        let mut json = String::from("[");
+        for (index, record) in self.selected(records).into_iter().enumerate() {
+            json.push_str(if index == 0 { "\n  {" } else { ",\n  {" });
+            for (index, (key, value)) in self.ordered(record).into_iter().enumerate() {
+                if index > 0 {
+                    json.push_str(", ");
+                }
+                let _ = write!(json, "{}: {}", json_string(key), json_string(value));
+            }
+            json.push('}');
+        }
+        json.push_str(if json.len() == 1 { "]\n" } else { "\n]\n" });
+        json
+    }
+
+    /// A header row naming every field seen, then one row per record with
+    /// empty cells for missing fields.
+    pub fn to_csv(&self, records: &[RawRecord]) -> String {
+        let selected = self.selected(records);
+        let mut columns: Vec<&str> = self.rules.fields().map(|x| x.field.as_str()).collect();
+        for record in &selected {
+            for (key, _) in record.pairs() {
+                if !columns.contains(&key) {
+                    columns.push(key);
+                }
+            }
+        }
+
+        let mut csv = String::new();
+        let row = |csv: &mut String, cells: Vec<&str>| {
+            let cells: Vec<String> = cells.into_iter().map(csv_cell).collect();
+            csv.push_str(&cells.join(","));
+            csv.push('\n');
+        };
+        row(&mut csv, columns.clone());
+        for record in selected {
+            let cells = columns.iter().map(|x| record.get(x).unwrap_or(""));
+            row(&mut csv, cells.collect());
+        }
+        csv
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn csv_cell(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+fn example() -> (Rules, Vec<RawRecord>) {
+    let rules = crate::rules::puzzle_rules();
+    let text = "hcl:#623a2f pid:087499704 hgt:74in ecl:grn\n\
+                iyr:2012 eyr:2030 byr:1980\n\
+                \n\
+                eyr:1972 cid:100 zip:\"00,100\"\n\
+                hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926\n";
+    let records = crate::parse::parse_batch(text, &rules, crate::parse::Mode::Lenient).unwrap();
+    (rules, records)
+}
+
+#[test]
+fn test_batch() {
+    let (rules, records) = example();
+    assert_eq!(
+        Export::new(&rules).to_batch(&records),
+        "byr:1980 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn pid:087499704\n\
+         \n\
+         byr:1926 iyr:2018 eyr:1972 hgt:170 hcl:#18171d ecl:amb pid:186cm cid:100 zip:\"00,100\"\n"
+    );
+
+    // Written batches read back to the same records.
+    let batch = Export::new(&rules).to_batch(&records);
+    let again = crate::parse::parse_batch(&batch, &rules, crate::parse::Mode::Lenient).unwrap();
+    assert_eq!(Export::new(&rules).to_batch(&again), batch);
+
+    let invalid = Export::new(&rules).only(Filter::Invalid);
+    assert!(invalid.to_batch(&records).starts_with("byr:1926"));
+    assert_eq!(invalid.to_batch(&records).lines().count(), 1);
+}
+
+#[test]
+fn test_json() {
+    let (rules, records) = example();
+    let valid = Export::new(&rules).only(Filter::Valid);
+    assert_eq!(valid.to_json(&records[1..]), "[]\n");
+    assert_eq!(
+        valid.to_json(&records),
+        "[\n  {\"byr\": \"1980\", \"iyr\": \"2012\", \"eyr\": \"2030\", \"hgt\": \"74in\", \
+         \"hcl\": \"#623a2f\", \"ecl\": \"grn\", \"pid\": \"087499704\"}\n]\n"
+    );
+    let invalid = Export::new(&rules).only(Filter::Invalid).to_json(&records);
+    assert!(invalid.ends_with(", \"cid\": \"100\", \"zip\": \"\\\"00,100\\\"\"}\n]\n"));
+}
+
+#[test]
+fn test_csv() {
+    let (rules, records) = example();
+    assert_eq!(
+        Export::new(&rules).render(Format::Csv, &records),
+        "byr,iyr,eyr,hgt,hcl,ecl,pid,cid,zip\n\
+         1980,2012,2030,74in,#623a2f,grn,087499704,,\n\
+         1926,2018,1972,170,#18171d,amb,186cm,100,\"\"\"00,100\"\"\"\n"
+    );
+    assert_eq!("csv".parse(), Ok(Format::Csv));
+    assert!("xml".parse::<Format>().is_err());
+    assert_eq!("valid".parse(), Ok(Filter::Valid));
+}
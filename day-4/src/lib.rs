@@ -0,0 +1,8 @@
+// --- Day 4: Passport Processing ---
+//
+// Pieces shared by the day 4 solutions.
+pub mod export;
+pub mod parse;
+pub mod passport;
+pub mod report;
+pub mod rules;
@@ -2,8 +2,14 @@
 //
 // https://adventofcode.com/2020/day/4
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::fs;
+use std::io;
+
+use day_4::export::{Export, Filter, Format};
+use day_4::parse::{self, Mode, RawRecord};
+use day_4::passport::{PassportError, ValidatedPassport};
+use day_4::report::Report;
+use day_4::rules::Rules;
 
 #[derive(Debug, PartialEq, Eq, Hash)]
 enum Field {
@@ -15,113 +21,105 @@ enum Field {
     Pid,
     Eyr,
     Cid,
+    Other(String),
+}
+impl Field {
+    fn from_key(key: &str) -> Field {
+        match key {
+            "byr" => Field::Byr,
+            "iyr" => Field::Iyr,
+            "eyr" => Field::Eyr,
+            "hgt" => Field::Hgt,
+            "hcl" => Field::Hcl,
+            "ecl" => Field::Ecl,
+            "pid" => Field::Pid,
+            "cid" => Field::Cid,
+            _ => Field::Other(key.to_string()),
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Field::Byr => "byr",
+            Field::Iyr => "iyr",
+            Field::Eyr => "eyr",
+            Field::Hgt => "hgt",
+            Field::Hcl => "hcl",
+            Field::Ecl => "ecl",
+            Field::Pid => "pid",
+            Field::Cid => "cid",
+            Field::Other(key) => key,
+        }
+    }
 }
+
 #[derive(Debug)]
-struct Passport(HashMap<Field, String>);
+struct Passport {
+    fields: HashMap<Field, String>,
+    /// The typed passport, built only once the rules accept the fields.
+    typed: Result<ValidatedPassport, PassportError>,
+}
 impl Passport {
-    fn is_valid(&self) -> bool {
-        self.0.len() == 8 || (self.0.len() == 7 && !self.0.contains_key(&Field::Cid))
+    fn is_valid(&self, rules: &Rules) -> bool {
+        rules.has_required(self.fields.keys().map(Field::name))
     }
 
     fn is_valid_strict(&self) -> bool {
-        self.is_valid() && {
-            self.0
-                .iter()
-                .map(|fv| is_field_valid(fv.0, fv.1))
-                .fold(true, |acc, x| acc && x)
-        }
+        self.typed.is_ok()
     }
 
-    fn from_seq(seq: &Vec<String>) -> Passport {
-        let mut pass = HashMap::new();
-        for line in seq {
-            for part in line.split(' ') {
-                let kv: Vec<_> = part.splitn(2, ':').collect();
-                let f = match kv[0] {
-                    "byr" => Field::Byr,
-                    "iyr" => Field::Iyr,
-                    "eyr" => Field::Eyr,
-                    "hgt" => Field::Hgt,
-                    "hcl" => Field::Hcl,
-                    "ecl" => Field::Ecl,
-                    "pid" => Field::Pid,
-                    "cid" => Field::Cid,
-                    _ => unreachable!(),
-                };
-                pass.insert(f, kv[1].to_string());
-            }
-        }
-        Passport(pass)
+    fn report(&self, rules: &Rules) -> Report {
+        rules.report(self.fields.iter().map(|fv| (fv.0.name(), fv.1.as_str())))
     }
-}
 
-fn is_field_valid(f: &Field, val: &str) -> bool {
-    match f {
-        Field::Byr => {
-            if let Ok(num) = val.parse::<i32>() {
-                num >= 1920 && num <= 2002
-            } else {
-                false
-            }
-        }
-        Field::Iyr => {
-            if let Ok(num) = val.parse::<i32>() {
-                num >= 2010 && num <= 2020
-            } else {
-                false
-            }
-        }
-        Field::Eyr => {
-            if let Ok(num) = val.parse::<i32>() {
-                num >= 2020 && num <= 2030
-            } else {
-                false
-            }
-        }
-        Field::Hgt => {
-            if val.ends_with("cm") {
-                if let Ok(num) = val.trim_end_matches("cm").parse::<i32>() {
-                    num >= 150 && num <= 193
-                } else {
-                    false
-                }
-            } else if val.ends_with("in") {
-                if let Ok(num) = val.trim_end_matches("in").parse::<i32>() {
-                    num >= 59 && num <= 76
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
+    fn from_record(record: &RawRecord, rules: &Rules) -> Passport {
+        Passport {
+            fields: record
+                .pairs()
+                .map(|(key, value)| (Field::from_key(key), value.to_string()))
+                .collect(),
+            typed: ValidatedPassport::checked(rules, record),
         }
-        Field::Hcl => {
-            val.len() == 7
-                && val.chars().nth(0).unwrap() == '#'
-                && val
-                    .chars()
-                    .skip(1)
-                    .map(|c| c.is_digit(10) || ['a', 'b', 'c', 'd', 'e', 'f'].contains(&c))
-                    .fold(true, |acc, x| acc && x)
-        }
-        Field::Ecl => ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"].contains(&val),
-        Field::Pid => {
-            val.len() == 9
-                && val
-                    .chars()
-                    .map(|c| c.is_digit(10))
-                    .fold(true, |acc, x| acc && x)
-        }
-        Field::Cid => true,
     }
 }
 
 fn main() {
-    let passports = input("src/matt.txt").unwrap();
+    let rules = Rules::load("rules.txt").unwrap();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // `--strict` refuses a batch with unknown, repeated or malformed keys.
+    let mode = if args.iter().any(|x| x == "--strict") {
+        Mode::Strict
+    } else {
+        Mode::Lenient
+    };
+
+    // `matt export FORMAT [all|valid|invalid]` rewrites the batch on stdout.
+    if args.first().map(String::as_str) == Some("export") {
+        let format: Format = args.get(1).map_or("batch", String::as_str).parse().unwrap();
+        let filter: Filter = args.get(2).map_or("all", String::as_str).parse().unwrap();
+        let records = records("src/matt.txt", &rules, mode).unwrap();
+        print!(
+            "{}",
+            Export::new(&rules).only(filter).render(format, &records)
+        );
+        return;
+    }
+
+    let passports = input("src/matt.txt", &rules, mode).unwrap();
+
+    // `matt report` explains what is wrong with every invalid passport.
+    if args.iter().any(|x| x == "report") {
+        for (index, report) in passports.iter().map(|x| x.report(&rules)).enumerate() {
+            if !report.is_valid() {
+                println!("Passport {}:\n{}", index + 1, report);
+            }
+        }
+        return;
+    }
 
     println!(
         "Part One: {} ",
-        passports.iter().filter(|x| x.is_valid()).count(),
+        passports.iter().filter(|x| x.is_valid(&rules)).count(),
     );
     println!(
         "Part Two: {} ",
@@ -129,29 +127,29 @@ fn main() {
     );
 }
 
-fn input(fname: &str) -> io::Result<Vec<Passport>> {
-    let mut input = vec![];
-    let file = File::open(fname)?;
-    let buf = io::BufReader::new(file);
-    let mut p = vec![];
-    for item in buf.lines() {
-        let line = item?;
-        if line.is_empty() {
-            input.push(Passport::from_seq(&p));
-            p.clear();
-        } else {
-            p.push(line);
-        }
+fn records(fname: &str, rules: &Rules, mode: Mode) -> io::Result<Vec<RawRecord>> {
+    let text = fs::read_to_string(fname)?;
+    let records = parse::parse_batch(&text, rules, mode)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    for problem in records.iter().flat_map(|x| &x.problems) {
+        eprintln!("{}: {}", fname, problem);
     }
-    input.push(Passport::from_seq(&p));
-    Ok(input)
+    Ok(records)
+}
+
+fn input(fname: &str, rules: &Rules, mode: Mode) -> io::Result<Vec<Passport>> {
+    Ok(records(fname, rules, mode)?
+        .iter()
+        .map(|x| Passport::from_record(x, rules))
+        .collect())
 }
 
 #[test]
 fn test_matt() -> io::Result<()> {
-    let valid = input("src/matt_test.txt")?
+    let rules = Rules::load("rules.txt").unwrap();
+    let valid = input("src/matt_test.txt", &rules, Mode::Strict)?
         .iter()
-        .filter(|x| x.is_valid())
+        .filter(|x| x.is_valid(&rules))
         .count();
     assert_eq!(valid, 2);
 
@@ -160,7 +158,8 @@ fn test_matt() -> io::Result<()> {
 
 #[test]
 fn test_valid() -> io::Result<()> {
-    let valid = input("src/matt_test_valid.txt")?
+    let rules = Rules::load("rules.txt").unwrap();
+    let valid = input("src/matt_test_valid.txt", &rules, Mode::Strict)?
         .iter()
         .filter(|x| x.is_valid_strict())
         .count();
@@ -168,3 +167,71 @@ fn test_valid() -> io::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+const EXTRA_KEY: &str = "byr:1980 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn pid:087499704\n\n\
+                         byr:1980 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn pid:087499704 zip:12345\n";
+
+#[test]
+fn test_extra_key() {
+    let rules = Rules::load("rules.txt").unwrap();
+    let passports: Vec<Passport> = parse::parse_batch(EXTRA_KEY, &rules, Mode::Lenient)
+        .unwrap()
+        .iter()
+        .map(|x| Passport::from_record(x, &rules))
+        .collect();
+    assert!(passports.iter().all(|x| x.is_valid(&rules)));
+    assert_eq!(passports.iter().filter(|x| x.is_valid_strict()).count(), 1);
+    assert_eq!(
+        passports[1].typed,
+        Err(PassportError::Rejected),
+        "the rules know no zip field"
+    );
+}
+
+#[test]
+fn test_loosened_rules() {
+    let text = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1910 hcl:#623a2f\n\
+                \n\
+                pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f\n";
+    let part_two = |rules: &Rules| {
+        parse::parse_batch(text, rules, Mode::Lenient)
+            .unwrap()
+            .iter()
+            .map(|x| Passport::from_record(x, rules))
+            .filter(|x| x.is_valid_strict())
+            .count()
+    };
+    let rules = Rules::load("rules.txt").unwrap();
+    assert_eq!(part_two(&rules), 1);
+
+    let loose: Rules = fs::read_to_string("rules.txt")
+        .unwrap()
+        .replace("byr range 1920 2002", "byr range 1900 2002")
+        .parse()
+        .unwrap();
+    assert_eq!(part_two(&loose), 2);
+}
+
+#[test]
+fn test_export_matches_part_two() -> io::Result<()> {
+    let rules = Rules::load("rules.txt").unwrap();
+    let records = records("src/matt.txt", &rules, Mode::Lenient)?;
+    let part_two = records
+        .iter()
+        .map(|x| Passport::from_record(x, &rules))
+        .filter(|x| x.is_valid_strict())
+        .count();
+    let count = |filter| {
+        Export::new(&rules)
+            .only(filter)
+            .to_csv(&records)
+            .lines()
+            .count()
+            - 1
+    };
+    assert_eq!(count(Filter::Valid), part_two);
+    assert_eq!(count(Filter::Invalid), records.len() - part_two);
+
+    Ok(())
+}
@@ -0,0 +1,231 @@
+// Reading batch files: records of `key:value` pairs, separated by blank lines.
+use std::error::Error;
+use std::fmt;
+
+use crate::rules::Rules;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Keep going past anything odd, noting it on the record.
+    Lenient,
+    /// Reject the batch at the first problem.
+    Strict,
+}
+
+/// Something odd about a record. Lines are counted from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// The rules do not know the key. The field is still kept.
+    UnknownKey { key: String, line: usize },
+    /// The key was already given; the first value is kept.
+    DuplicateKey {
+        key: String,
+        line: usize,
+        first: usize,
+    },
+    /// A token with no `:` between key and value. It is dropped.
+    MissingSeparator { token: String, line: usize },
+}
+
+impl Problem {
+    pub fn line(&self) -> usize {
+        match self {
+            Problem::UnknownKey { line, .. }
+            | Problem::DuplicateKey { line, .. }
+            | Problem::MissingSeparator { line, .. } => *line,
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::UnknownKey { key, line } => write!(f, "line {}: unknown key {:?}", line, key),
+            Problem::DuplicateKey { key, line, first } => write!(
+                f,
+                "line {}: duplicate key {:?}, first given on line {}",
+                line, key, first
+            ),
+            Problem::MissingSeparator { token, line } => {
+                write!(f, "line {}: no ':' in {:?}", line, token)
+            }
+        }
+    }
+}
+
+/// The problem that made a strict parse give up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError(pub Problem);
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawField {
+    pub key: String,
+    pub value: String,
+    pub line: usize,
+}
+
+/// One record as written, before any validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawRecord {
+    /// The line the record starts on.
+    pub line: usize,
+    /// Fields in the order given, without the dropped duplicates.
+    pub fields: Vec<RawField>,
+    pub problems: Vec<Problem>,
+}
+
+impl RawRecord {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|x| x.key == key)
+            .map(|x| x.value.as_str())
+    }
+
+    /// `(key, value)` pairs, ready for `Rules::report` and friends.
+    pub fn pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.fields
+            .iter()
+            .map(|x| (x.key.as_str(), x.value.as_str()))
+    }
+}
+
+/// Splits a batch file into records, checking keys against `rules`.
+///
+/// In `Mode::Strict` the first problem is an error; in `Mode::Lenient` the
+/// problems are listed on each record and parsing carries on.
+pub fn parse_batch(text: &str, rules: &Rules, mode: Mode) -> Result<Vec<RawRecord>, ParseError> {
+    let mut records = Vec::new();
+    let mut record: Option<RawRecord> = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        if line.trim().is_empty() {
+            records.extend(record.take());
+            continue;
+        }
+        let record = record.get_or_insert_with(|| RawRecord {
+            line: number,
+            fields: Vec::new(),
+            problems: Vec::new(),
+        });
+
+        for token in line.split_whitespace() {
+            let (key, value) = match token.find(':') {
+                Some(at) => (&token[..at], &token[at + 1..]),
+                None => {
+                    record.problems.push(Problem::MissingSeparator {
+                        token: token.to_string(),
+                        line: number,
+                    });
+                    continue;
+                }
+            };
+
+            if let Some(first) = record.fields.iter().find(|x| x.key == key) {
+                let problem = Problem::DuplicateKey {
+                    key: key.to_string(),
+                    line: number,
+                    first: first.line,
+                };
+                record.problems.push(problem);
+                continue;
+            }
+            if rules.get(key).is_none() {
+                record.problems.push(Problem::UnknownKey {
+                    key: key.to_string(),
+                    line: number,
+                });
+            }
+            record.fields.push(RawField {
+                key: key.to_string(),
+                value: value.to_string(),
+                line: number,
+            });
+        }
+
+        if mode == Mode::Strict {
+            if let Some(problem) = record.problems.first() {
+                return Err(ParseError(problem.clone()));
+            }
+        }
+    }
+    records.extend(record);
+    Ok(records)
+}
+
+#[cfg(test)]
+const MESSY: &str = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+iyr:2013 ecl:amb zip:00100 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929 ecl:blu
+
+
+hcl:#ae17e1 iyr:2013 eyr2024
+ecl:brn pid:760753108 byr:1931 hgt:179cm
+";
+
+#[test]
+fn test_lenient() {
+    let rules = crate::rules::puzzle_rules();
+    let records = parse_batch(MESSY, &rules, Mode::Lenient).unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(
+        records.iter().map(|x| x.line).collect::<Vec<_>>(),
+        vec![1, 4, 8]
+    );
+
+    assert!(records[0].problems.is_empty());
+    assert_eq!(records[0].fields.len(), 8);
+    assert_eq!(records[0].fields[7].line, 2);
+
+    assert_eq!(
+        records[1].problems,
+        vec![
+            Problem::UnknownKey {
+                key: "zip".to_string(),
+                line: 4
+            },
+            Problem::DuplicateKey {
+                key: "ecl".to_string(),
+                line: 5,
+                first: 4
+            },
+        ]
+    );
+    assert_eq!(records[1].get("ecl"), Some("amb"));
+    assert_eq!(records[1].get("zip"), Some("00100"));
+    assert!(!rules.validate(records[1].pairs()));
+
+    assert_eq!(
+        records[2].problems[0].to_string(),
+        "line 8: no ':' in \"eyr2024\""
+    );
+    assert_eq!(records[2].get("eyr"), None);
+}
+
+#[test]
+fn test_strict() {
+    let rules = crate::rules::puzzle_rules();
+    let error = parse_batch(MESSY, &rules, Mode::Strict).unwrap_err();
+    assert_eq!(error.0.line(), 4);
+    assert_eq!(error.to_string(), "line 4: unknown key \"zip\"");
+
+    let clean = "byr:1937 iyr:2017\nhgt:183cm\n\n\nbyr:1931";
+    let records = parse_batch(clean, &rules, Mode::Strict).unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].line, 5);
+    assert_eq!(
+        records[1].pairs().collect::<Vec<_>>(),
+        vec![("byr", "1931")]
+    );
+}
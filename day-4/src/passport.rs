@@ -0,0 +1,421 @@
+// Passports with every value in a proper type, rather than as text.
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::parse::RawRecord;
+use crate::rules::Rules;
+
+const CM_PER_INCH: f64 = 2.54;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Cm,
+    In,
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Unit::Cm => "cm",
+            Unit::In => "in",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Height {
+    pub value: u32,
+    pub unit: Unit,
+}
+
+impl Height {
+    pub fn cm(&self) -> f64 {
+        match self.unit {
+            Unit::Cm => self.value as f64,
+            Unit::In => self.value as f64 * CM_PER_INCH,
+        }
+    }
+
+    pub fn inches(&self) -> f64 {
+        self.cm() / CM_PER_INCH
+    }
+
+    /// The same height in `unit`, rounded to the nearest whole one.
+    pub fn to_unit(self, unit: Unit) -> Height {
+        let value = match unit {
+            Unit::Cm => self.cm(),
+            Unit::In => self.inches(),
+        };
+        Height {
+            value: value.round() as u32,
+            unit,
+        }
+    }
+}
+
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.value, self.unit)
+    }
+}
+
+impl FromStr for Height {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Height, ()> {
+        let (value, unit) = if let Some(value) = s.strip_suffix("cm") {
+            (value, Unit::Cm)
+        } else if let Some(value) = s.strip_suffix("in") {
+            (value, Unit::In)
+        } else {
+            return Err(());
+        };
+        Ok(Height {
+            value: digits(value, 1..=9)?,
+            unit,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HairColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+/// Written back as `#rrggbb`.
+impl fmt::Display for HairColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl FromStr for HairColor {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<HairColor, ()> {
+        let hex = s.strip_prefix('#').ok_or(())?;
+        let lower = |b: u8| b.is_ascii_digit() || (b'a'..=b'f').contains(&b);
+        if hex.len() != 6 || !hex.bytes().all(lower) {
+            return Err(());
+        }
+        let byte = |at: usize| u8::from_str_radix(&hex[at..at + 2], 16).map_err(|_| ());
+        Ok(HairColor {
+            r: byte(0)?,
+            g: byte(2)?,
+            b: byte(4)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EyeColor {
+    Amber,
+    Blue,
+    Brown,
+    Grey,
+    Green,
+    Hazel,
+    Other,
+}
+
+impl EyeColor {
+    pub const ALL: [EyeColor; 7] = [
+        EyeColor::Amber,
+        EyeColor::Blue,
+        EyeColor::Brown,
+        EyeColor::Grey,
+        EyeColor::Green,
+        EyeColor::Hazel,
+        EyeColor::Other,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            EyeColor::Amber => "amb",
+            EyeColor::Blue => "blu",
+            EyeColor::Brown => "brn",
+            EyeColor::Grey => "gry",
+            EyeColor::Green => "grn",
+            EyeColor::Hazel => "hzl",
+            EyeColor::Other => "oth",
+        }
+    }
+}
+
+impl fmt::Display for EyeColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for EyeColor {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<EyeColor, ()> {
+        EyeColor::ALL
+            .iter()
+            .find(|x| x.code() == s)
+            .copied()
+            .ok_or(())
+    }
+}
+
+/// Nine digits, kept as written so leading zeros survive.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PassportId(String);
+
+impl PassportId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn number(&self) -> u32 {
+        self.0.parse().unwrap()
+    }
+}
+
+impl fmt::Display for PassportId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for PassportId {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<PassportId, ()> {
+        digits::<u32>(s, 9..=9)?;
+        Ok(PassportId(s.to_string()))
+    }
+}
+
+/// A whole number written with a digit count in `len`, and nothing else.
+fn digits<T: FromStr>(s: &str, len: std::ops::RangeInclusive<usize>) -> Result<T, ()> {
+    if !len.contains(&s.len()) || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(());
+    }
+    s.parse().map_err(|_| ())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PassportError {
+    /// The rules turned the passport down before it was converted.
+    Rejected,
+    Missing(&'static str),
+    Invalid {
+        field: &'static str,
+        value: String,
+    },
+}
+
+impl fmt::Display for PassportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PassportError::Rejected => f.write_str("the rules reject it"),
+            PassportError::Missing(field) => write!(f, "{} is missing", field),
+            PassportError::Invalid { field, value } => {
+                write!(f, "{:?} is not a valid {}", value, field)
+            }
+        }
+    }
+}
+
+impl Error for PassportError {}
+
+/// A passport whose every field has the right shape.
+///
+/// Converting only checks that values are well formed; whether a year or
+/// height is in range is up to the rules file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedPassport {
+    pub birth_year: u16,
+    pub issue_year: u16,
+    pub expiration_year: u16,
+    pub height: Height,
+    pub hair_color: HairColor,
+    pub eye_color: EyeColor,
+    pub passport_id: PassportId,
+    pub country_id: Option<String>,
+}
+
+impl ValidatedPassport {
+    /// Converts `(key, value)` pairs; keys it has no field for are ignored.
+    pub fn from_pairs<'a, I: IntoIterator<Item = (&'a str, &'a str)>>(
+        pairs: I,
+    ) -> Result<ValidatedPassport, PassportError> {
+        let pairs: Vec<_> = pairs.into_iter().collect();
+        fn field<T: FromStr>(
+            pairs: &[(&str, &str)],
+            key: &'static str,
+        ) -> Result<T, PassportError> {
+            let value = pairs
+                .iter()
+                .find(|x| x.0 == key)
+                .ok_or(PassportError::Missing(key))?
+                .1;
+            value.parse().map_err(|_| PassportError::Invalid {
+                field: key,
+                value: value.to_string(),
+            })
+        }
+        let year = |key| -> Result<u16, PassportError> {
+            let year: String = field(&pairs, key)?;
+            digits(&year, 4..=4).map_err(|_| PassportError::Invalid {
+                field: key,
+                value: year,
+            })
+        };
+
+        Ok(ValidatedPassport {
+            birth_year: year("byr")?,
+            issue_year: year("iyr")?,
+            expiration_year: year("eyr")?,
+            height: field(&pairs, "hgt")?,
+            hair_color: field(&pairs, "hcl")?,
+            eye_color: field(&pairs, "ecl")?,
+            passport_id: field(&pairs, "pid")?,
+            country_id: pairs.iter().find(|x| x.0 == "cid").map(|x| x.1.to_string()),
+        })
+    }
+
+    /// Converts `record` once `rules` accept it, failing with `Rejected`
+    /// if they do not.
+    ///
+    /// The rules are checked first, so they decide every range and pattern.
+    /// Rules looser than these types, such as a `pid` of any length, pass
+    /// passports that then fail to convert.
+    pub fn checked(rules: &Rules, record: &RawRecord) -> Result<ValidatedPassport, PassportError> {
+        if !rules.validate(record.pairs()) {
+            return Err(PassportError::Rejected);
+        }
+        ValidatedPassport::try_from(record)
+    }
+
+    /// `(key, value)` pairs in canonical order, `cid` last if present.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![
+            ("byr", self.birth_year.to_string()),
+            ("iyr", self.issue_year.to_string()),
+            ("eyr", self.expiration_year.to_string()),
+            ("hgt", self.height.to_string()),
+            ("hcl", self.hair_color.to_string()),
+            ("ecl", self.eye_color.to_string()),
+            ("pid", self.passport_id.to_string()),
+        ];
+        if let Some(cid) = &self.country_id {
+            fields.push(("cid", cid.clone()));
+        }
+        fields
+    }
+}
+
+impl TryFrom<&RawRecord> for ValidatedPassport {
+    type Error = PassportError;
+
+    fn try_from(record: &RawRecord) -> Result<ValidatedPassport, PassportError> {
+        ValidatedPassport::from_pairs(record.pairs())
+    }
+}
+
+#[test]
+fn test_values() {
+    assert_eq!(
+        "183cm".parse(),
+        Ok(Height {
+            value: 183,
+            unit: Unit::Cm
+        })
+    );
+    assert!("183".parse::<Height>().is_err());
+    assert!("cm".parse::<Height>().is_err());
+    assert!("-3in".parse::<Height>().is_err());
+
+    let tall: Height = "76in".parse().unwrap();
+    assert_eq!(tall.to_unit(Unit::Cm).to_string(), "193cm");
+    assert_eq!(tall.to_unit(Unit::Cm).to_unit(Unit::In), tall);
+    assert!((tall.cm() - 193.04).abs() < 1e-9);
+
+    let hair: HairColor = "#0a7fff".parse().unwrap();
+    assert_eq!((hair.r, hair.g, hair.b), (10, 127, 255));
+    assert_eq!(hair.to_string(), "#0a7fff");
+    assert!("#0A7FFF".parse::<HairColor>().is_err());
+    assert!("0a7fff".parse::<HairColor>().is_err());
+
+    assert_eq!("hzl".parse(), Ok(EyeColor::Hazel));
+    assert!("wat".parse::<EyeColor>().is_err());
+
+    let pid: PassportId = "000000001".parse().unwrap();
+    assert_eq!(pid.to_string(), "000000001");
+    assert_eq!(pid.number(), 1);
+    assert!("0123456789".parse::<PassportId>().is_err());
+}
+
+#[test]
+fn test_try_from() {
+    let rules = crate::rules::puzzle_rules();
+    let text = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980\n\
+                hcl:#623a2f\n\
+                \n\
+                eyr:2029 ecl:blu cid:129 byr:1989\n\
+                iyr:2014 pid:896056539 hcl:#a97842 hgt:165\n";
+    let records = crate::parse::parse_batch(text, &rules, crate::parse::Mode::Strict).unwrap();
+
+    let passport = ValidatedPassport::try_from(&records[0]).unwrap();
+    assert_eq!(passport.birth_year, 1980);
+    assert_eq!(passport.height.to_unit(Unit::Cm).value, 188);
+    assert_eq!(passport.eye_color, EyeColor::Green);
+    assert_eq!(passport.passport_id.as_str(), "087499704");
+    assert_eq!(passport.country_id, None);
+    assert_eq!(
+        passport.fields()[..4],
+        [
+            ("byr", "1980".to_string()),
+            ("iyr", "2012".to_string()),
+            ("eyr", "2030".to_string()),
+            ("hgt", "74in".to_string()),
+        ]
+    );
+
+    assert_eq!(
+        ValidatedPassport::try_from(&records[1]),
+        Err(PassportError::Invalid {
+            field: "hgt",
+            value: "165".to_string()
+        })
+    );
+    assert_eq!(
+        ValidatedPassport::from_pairs(vec![("byr", "1980")]),
+        Err(PassportError::Missing("iyr"))
+    );
+    assert!(ValidatedPassport::from_pairs(vec![("byr", "80")]).is_err());
+}
+
+#[test]
+fn test_checked() {
+    let strict = crate::rules::puzzle_rules();
+    let loose: Rules = include_str!("../rules.txt")
+        .replace("pid regex [0-9]{9}", "pid regex [0-9]+")
+        .parse()
+        .unwrap();
+    let text = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f\n\
+                \n\
+                pid:0874997041 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f\n";
+    let records = crate::parse::parse_batch(text, &strict, crate::parse::Mode::Strict).unwrap();
+
+    assert!(ValidatedPassport::checked(&strict, &records[0]).is_ok());
+    assert_eq!(
+        ValidatedPassport::checked(&strict, &records[1]),
+        Err(PassportError::Rejected)
+    );
+    assert_eq!(
+        ValidatedPassport::checked(&loose, &records[1]),
+        Err(PassportError::Invalid {
+            field: "pid",
+            value: "0874997041".to_string()
+        })
+    );
+}
@@ -0,0 +1,188 @@
+// Per-field results of checking one document against the rules.
+use std::fmt;
+
+use crate::rules::Rules;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Valid,
+    /// A required field is absent.
+    Missing,
+    /// The field is present but its value breaks the rule.
+    Invalid,
+    /// The rules do not know the field at all.
+    Unknown,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Status::Valid => "ok",
+            Status::Missing => "missing",
+            Status::Invalid => "invalid",
+            Status::Unknown => "unknown",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldReport {
+    pub field: String,
+    pub status: Status,
+    pub value: Option<String>,
+    /// The rule the value is checked against, in rules file syntax.
+    pub rule: Option<String>,
+}
+
+/// One row per field: those the rules know in rules order, then unknown
+/// ones in document order. Absent optional fields are left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub fields: Vec<FieldReport>,
+}
+
+impl Report {
+    pub fn is_valid(&self) -> bool {
+        self.fields.iter().all(|x| x.status == Status::Valid)
+    }
+
+    /// Whether every required field is present, whatever its value.
+    pub fn is_complete(&self) -> bool {
+        self.with_status(Status::Missing).next().is_none()
+    }
+
+    pub fn with_status(&self, status: Status) -> impl Iterator<Item = &FieldReport> {
+        self.fields.iter().filter(move |x| x.status == status)
+    }
+}
+
+impl Rules {
+    pub fn report<'a, I: IntoIterator<Item = (&'a str, &'a str)>>(&self, fields: I) -> Report {
+        let fields: Vec<_> = fields.into_iter().collect();
+        let mut report = Vec::new();
+
+        for rule in self.fields() {
+            let value = fields.iter().find(|x| x.0 == rule.field).map(|x| x.1);
+            let status = match value {
+                None if rule.required => Status::Missing,
+                None => continue,
+                Some(value) if rule.rule.check(value) => Status::Valid,
+                Some(_) => Status::Invalid,
+            };
+            report.push(FieldReport {
+                field: rule.field.clone(),
+                status,
+                value: value.map(str::to_string),
+                rule: Some(rule.rule.to_string()),
+            });
+        }
+
+        for &(field, value) in fields.iter().filter(|x| self.get(x.0).is_none()) {
+            report.push(FieldReport {
+                field: field.to_string(),
+                status: Status::Unknown,
+                value: Some(value.to_string()),
+                rule: None,
+            });
+        }
+        Report { fields: report }
+    }
+}
+
+/// An aligned table, ready to hand to an applicant.
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = ["Field", "Status", "Value", "Rule"];
+        let rows: Vec<[String; 4]> = self
+            .fields
+            .iter()
+            .map(|x| {
+                [
+                    x.field.clone(),
+                    x.status.to_string(),
+                    x.value.clone().unwrap_or_default(),
+                    x.rule.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+
+        let mut widths = header.map(str::len);
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut line = |cells: [&str; 4]| -> fmt::Result {
+            let text = format!(
+                "{:w0$}  {:w1$}  {:w2$}  {}",
+                cells[0],
+                cells[1],
+                cells[2],
+                cells[3],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2]
+            );
+            writeln!(f, "{}", text.trim_end())
+        };
+        line(header)?;
+        line(widths.map(|w| "-".repeat(w)).each_ref().map(String::as_str))?;
+        for row in &rows {
+            line(row.each_ref().map(String::as_str))?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_report() {
+    let rules = crate::rules::puzzle_rules();
+    let report = rules.report(vec![
+        ("hgt", "190in"),
+        ("byr", "1980"),
+        ("iyr", "2012"),
+        ("eyr", "2030"),
+        ("hcl", "#623a2f"),
+        ("ecl", "grn"),
+        ("zip", "00100"),
+    ]);
+    assert!(!report.is_valid());
+    assert!(!report.is_complete());
+
+    let fields = |status| -> Vec<&str> {
+        report
+            .with_status(status)
+            .map(|x| x.field.as_str())
+            .collect()
+    };
+    assert_eq!(fields(Status::Missing), vec!["pid"]);
+    assert_eq!(fields(Status::Invalid), vec!["hgt"]);
+    assert_eq!(fields(Status::Unknown), vec!["zip"]);
+    assert_eq!(fields(Status::Valid).len(), 5);
+
+    assert_eq!(
+        report.to_string(),
+        "Field  Status   Value    Rule\n\
+         -----  -------  -------  --------------------------------\n\
+         byr    ok       1980     range 1920 2002\n\
+         iyr    ok       2012     range 2010 2020\n\
+         eyr    ok       2030     range 2020 2030\n\
+         hgt    invalid  190in    units cm 150 193 in 59 76\n\
+         hcl    ok       #623a2f  regex #[0-9a-f]{6}\n\
+         ecl    ok       grn      enum amb blu brn gry grn hzl oth\n\
+         pid    missing           regex [0-9]{9}\n\
+         zip    unknown  00100\n"
+    );
+
+    let report = rules.report(vec![
+        ("pid", "087499704"),
+        ("hgt", "74in"),
+        ("ecl", "grn"),
+        ("iyr", "2012"),
+        ("eyr", "2030"),
+        ("byr", "1980"),
+        ("hcl", "#623a2f"),
+    ]);
+    assert!(report.is_valid());
+}
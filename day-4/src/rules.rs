@@ -0,0 +1,295 @@
+// Passport validation rules, read from a rules file such as `rules.txt`.
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use regex::Regex;
+
+/// What a single field's value has to look like.
+#[derive(Debug, Clone)]
+pub enum Rule {
+    /// A whole number in `low..=high`.
+    Range(u32, u32),
+    /// A whole number followed by one of the units, in that unit's range.
+    Units(Vec<(String, u32, u32)>),
+    /// One of the listed words.
+    Enum(Vec<String>),
+    /// The value matches `regex`, which `Rule::regex` builds from `source`
+    /// so that it has to match the whole value.
+    Regex {
+        source: String,
+        regex: Regex,
+    },
+    Any,
+}
+
+fn parse_number(value: &str) -> Option<u32> {
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok()
+}
+
+impl Rule {
+    /// A rule that the whole value matches `source`.
+    pub fn regex(source: &str) -> Result<Rule, regex::Error> {
+        Ok(Rule::Regex {
+            source: source.to_string(),
+            regex: Regex::new(&format!("^(?:{})$", source))?,
+        })
+    }
+
+    pub fn check(&self, value: &str) -> bool {
+        match self {
+            Rule::Range(low, high) => {
+                parse_number(value).is_some_and(|n| (*low..=*high).contains(&n))
+            }
+            Rule::Units(units) => units.iter().any(|(unit, low, high)| {
+                value
+                    .strip_suffix(unit.as_str())
+                    .and_then(parse_number)
+                    .is_some_and(|n| (*low..=*high).contains(&n))
+            }),
+            Rule::Enum(words) => words.iter().any(|word| word == value),
+            Rule::Regex { regex, .. } => regex.is_match(value),
+            Rule::Any => true,
+        }
+    }
+}
+
+/// Writes the rule back in rules file syntax.
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::Range(low, high) => write!(f, "range {} {}", low, high),
+            Rule::Units(units) => {
+                f.write_str("units")?;
+                for (unit, low, high) in units {
+                    write!(f, " {} {} {}", unit, low, high)?;
+                }
+                Ok(())
+            }
+            Rule::Enum(words) => write!(f, "enum {}", words.join(" ")),
+            Rule::Regex { source, .. } => write!(f, "regex {}", source),
+            Rule::Any => f.write_str("any"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldRule {
+    pub field: String,
+    pub required: bool,
+    pub rule: Rule,
+}
+
+/// Every field a document may have, and what makes each one valid.
+#[derive(Debug, Clone)]
+pub struct Rules {
+    fields: Vec<FieldRule>,
+}
+
+#[derive(Debug)]
+pub enum RulesError {
+    Io(io::Error),
+    Syntax { line: usize, message: String },
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RulesError::Io(e) => write!(f, "unable to read rules: {}", e),
+            RulesError::Syntax { line, message } => write!(f, "rules line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for RulesError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RulesError::Io(e) => Some(e),
+            RulesError::Syntax { .. } => None,
+        }
+    }
+}
+
+impl Rules {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Rules, RulesError> {
+        fs::read_to_string(path).map_err(RulesError::Io)?.parse()
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = &FieldRule> {
+        self.fields.iter()
+    }
+
+    pub fn get(&self, field: &str) -> Option<&FieldRule> {
+        self.fields.iter().find(|rule| rule.field == field)
+    }
+
+    /// Whether `value` is valid for `field`. Fields without a rule never are.
+    pub fn check(&self, field: &str, value: &str) -> bool {
+        self.get(field).is_some_and(|rule| rule.rule.check(value))
+    }
+
+    /// Whether every required field is among `present`.
+    pub fn has_required<'a, I: IntoIterator<Item = &'a str>>(&self, present: I) -> bool {
+        let present: Vec<&str> = present.into_iter().collect();
+        self.fields
+            .iter()
+            .filter(|rule| rule.required)
+            .all(|rule| present.contains(&rule.field.as_str()))
+    }
+
+    /// Whether a document with these `(field, value)` pairs is valid: every
+    /// required field is there, and every field passes its rule. See
+    /// `report` for which fields are at fault.
+    ///
+    /// Part Two in both solutions and `Export`'s filters all come down to
+    /// this, by way of `ValidatedPassport::checked`.
+    pub fn validate<'a, I: IntoIterator<Item = (&'a str, &'a str)>>(&self, fields: I) -> bool {
+        self.report(fields).is_valid()
+    }
+}
+
+fn parse_rule(kind: &str, args: &[&str]) -> Result<Rule, String> {
+    let number =
+        |arg: &str| parse_number(arg).ok_or_else(|| format!("expected a number, found {:?}", arg));
+    match (kind, args) {
+        ("range", [low, high]) => Ok(Rule::Range(number(low)?, number(high)?)),
+        ("range", _) => Err("range takes LOW HIGH".to_string()),
+        ("units", _) if !args.is_empty() && args.len().is_multiple_of(3) => args
+            .chunks(3)
+            .map(|x| Ok((x[0].to_string(), number(x[1])?, number(x[2])?)))
+            .collect::<Result<_, String>>()
+            .map(Rule::Units),
+        ("units", _) => Err("units takes UNIT LOW HIGH, one or more times".to_string()),
+        ("enum", _) if !args.is_empty() => {
+            Ok(Rule::Enum(args.iter().map(|x| x.to_string()).collect()))
+        }
+        ("enum", _) => Err("enum takes at least one word".to_string()),
+        ("regex", [pattern]) => Rule::regex(pattern).map_err(|e| e.to_string()),
+        ("regex", _) => Err("regex takes a single PATTERN".to_string()),
+        ("any", []) => Ok(Rule::Any),
+        ("any", _) => Err("any takes no arguments".to_string()),
+        _ => Err(format!("unknown rule kind {:?}", kind)),
+    }
+}
+
+impl FromStr for Rules {
+    type Err = RulesError;
+
+    fn from_str(s: &str) -> Result<Rules, RulesError> {
+        let mut fields: Vec<FieldRule> = Vec::new();
+        for (index, line) in s.lines().enumerate() {
+            let syntax = |message: String| RulesError::Syntax {
+                line: index + 1,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut words: Vec<&str> = line.split_whitespace().collect();
+            let field = words.remove(0);
+            let required = words.first() != Some(&"optional");
+            if !required {
+                words.remove(0);
+            }
+            if words.is_empty() {
+                return Err(syntax(format!("no rule for {}", field)));
+            }
+            let kind = words.remove(0);
+            let rule = parse_rule(kind, &words).map_err(syntax)?;
+
+            if fields.iter().any(|rule| rule.field == field) {
+                return Err(syntax(format!("{} is defined twice", field)));
+            }
+            fields.push(FieldRule {
+                field: field.to_string(),
+                required,
+                rule,
+            });
+        }
+        Ok(Rules { fields })
+    }
+}
+
+/// The rules in `rules.txt`, which the puzzle answers use.
+#[cfg(test)]
+pub(crate) fn puzzle_rules() -> Rules {
+    include_str!("../rules.txt").parse().unwrap()
+}
+
+#[test]
+fn test_puzzle_rules() {
+    let rules = puzzle_rules();
+    let cases = [
+        ("byr", "2002", true),
+        ("byr", "2003", false),
+        ("byr", "+2000", false),
+        ("hgt", "60in", true),
+        ("hgt", "190cm", true),
+        ("hgt", "190in", false),
+        ("hgt", "190", false),
+        ("hcl", "#123abc", true),
+        ("hcl", "#123abz", false),
+        ("hcl", "123abc", false),
+        ("ecl", "brn", true),
+        ("ecl", "wat", false),
+        ("pid", "000000001", true),
+        ("pid", "0123456789", false),
+        ("cid", "anything", true),
+        ("xyz", "1", false),
+    ];
+    for &(field, value, valid) in cases.iter() {
+        assert_eq!(rules.check(field, value), valid, "{}:{}", field, value);
+    }
+
+    assert!(rules.has_required(vec!["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"]));
+    assert!(!rules.has_required(vec!["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "cid"]));
+    assert_eq!(
+        rules.get("hgt").unwrap().rule.to_string(),
+        "units cm 150 193 in 59 76"
+    );
+    assert_eq!(rules.get("pid").unwrap().rule.to_string(), "regex [0-9]{9}");
+}
+
+#[test]
+fn test_rules_errors() {
+    let error = |s: &str| match s.parse::<Rules>() {
+        Err(RulesError::Syntax { line, .. }) => line,
+        other => panic!("expected a syntax error, got {:?}", other),
+    };
+    assert_eq!(error("byr range 1920"), 1);
+    assert_eq!(error("# rules\nbyr range 1920 x"), 2);
+    assert_eq!(error("hgt units cm 150"), 1);
+    assert_eq!(error("hcl regex #[0-9"), 1);
+    assert_eq!(error("ecl colour red"), 1);
+    assert_eq!(error("cid optional"), 1);
+    assert_eq!(error("byr any\nbyr any"), 2);
+
+    let rules: Rules = "visa optional enum work study\nname any".parse().unwrap();
+    assert!(rules.validate(vec![("name", "Ann")]));
+    assert!(!rules.validate(vec![("name", "Ann"), ("visa", "tourist")]));
+    assert!(!rules.validate(vec![("visa", "work")]));
+}
+
+#[test]
+fn test_regex_display() {
+    let rule = Rule::regex("[0-9]{9}").unwrap();
+    assert_eq!(rule.to_string(), "regex [0-9]{9}");
+    assert!(rule.check("000000001"));
+    assert!(!rule.check("0000000012"));
+
+    // Built by hand, so neither anchored nor wrapped.
+    let loose = Rule::Regex {
+        source: "a".to_string(),
+        regex: Regex::new("a").unwrap(),
+    };
+    assert_eq!(loose.to_string(), "regex a");
+    assert!(loose.check("bab"));
+}
@@ -1,35 +1,24 @@
-use std::fmt;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::str::FromStr;
+use std::fs;
 
-use itertools::Itertools;
-
-#[derive(Debug)]
-enum Entry {
-    StrVal(String),
-    IntVal(u32),
-}
+use day_4::parse::{self, Mode, RawRecord};
+use day_4::passport::{PassportError, ValidatedPassport};
+use day_4::report::Report;
+use day_4::rules::Rules;
 
 #[derive(Default, Debug)]
 struct Passport {
-    birth_year: Option<Entry>,
-    issue_year: Option<Entry>,
-    exp_year: Option<Entry>,
-    height: Option<Entry>,
-    hair_color: Option<Entry>,
-    eye_color: Option<Entry>,
-    pid: Option<Entry>,
-    cid: Option<Entry>,
-}
-
-#[derive(Debug)]
-struct PassParseError;
-
-impl fmt::Display for PassParseError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str("Invalid Passport String")
-    }
+    birth_year: Option<String>,
+    issue_year: Option<String>,
+    exp_year: Option<String>,
+    height: Option<String>,
+    hair_color: Option<String>,
+    eye_color: Option<String>,
+    pid: Option<String>,
+    cid: Option<String>,
+    // Keys the rules may not know, kept so the report can flag them.
+    others: Vec<(String, String)>,
+    // Only filled in once the rules accept the passport.
+    typed: Option<ValidatedPassport>,
 }
 
 impl Passport {
@@ -37,184 +26,101 @@ impl Passport {
         Default::default()
     }
 
-    fn is_valid(&self) -> bool {
-        // Checks whether all entries exist
-
-        let test = self
-            .birth_year
-            .as_ref()
-            .zip(self.issue_year.as_ref())
-            .zip(self.exp_year.as_ref())
-            .zip(self.height.as_ref())
-            .zip(self.hair_color.as_ref())
-            .zip(self.eye_color.as_ref())
-            .zip(self.pid.as_ref());
-
-        test.is_some()
-    }
-
-    fn validate_ints(value: u32, lower: u32, higher: u32) -> bool {
-        value >= lower && value <= higher
-    }
-
-    fn validate_limits(value: &Option<Entry>, lower: u32, higher: u32) -> bool {
-        use Entry::IntVal;
-        match value {
-            Some(IntVal(value)) => Passport::validate_ints(*value, lower, higher),
-            _ => false,
-        }
-    }
-
-    fn validate_height(value: &Option<Entry>) -> bool {
-        use Entry::StrVal;
-        match value {
-            Some(StrVal(value_str)) => {
-                let index = value_str.chars().count() - 2;
-                let slice = &value_str[..index];
-
-                match slice.parse::<u32>() {
-                    Ok(height) => match &value_str[index..] {
-                        "cm" => Passport::validate_ints(height, 150, 193),
-                        "in" => Passport::validate_ints(height, 59, 76),
-                        _ => false,
-                    },
-                    Err(_) => false,
-                }
-            }
-            _ => false,
-        }
-    }
-
-    fn validate_hair_color(value: &Option<Entry>) -> bool {
-        use Entry::StrVal;
-        match value {
-            Some(StrVal(value_str)) => {
-                value_str.starts_with("#")
-                    && value_str[1..]
-                        .chars()
-                        .map(|c| "0123456789abcdef".contains(c))
-                        .all(|x| x)
-            }
-            _ => false,
-        }
-    }
-
-    fn validate_eye_color(value: &Option<Entry>) -> bool {
-        use Entry::StrVal;
-        match value {
-            Some(StrVal(value_str)) => {
-                let slice = &value_str[..];
-                vec!["amb", "blu", "brn", "gry", "grn", "hzl", "oth"].contains(&slice)
-            }
-            _ => false,
-        }
+    fn fields(&self) -> Vec<(&str, &str)> {
+        let known = vec![
+            ("byr", &self.birth_year),
+            ("iyr", &self.issue_year),
+            ("eyr", &self.exp_year),
+            ("hgt", &self.height),
+            ("hcl", &self.hair_color),
+            ("ecl", &self.eye_color),
+            ("pid", &self.pid),
+            ("cid", &self.cid),
+        ]
+        .into_iter()
+        .filter_map(|(name, entry)| entry.as_deref().map(|entry| (name, entry)));
+        let others = self
+            .others
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()));
+
+        known.chain(others).collect()
     }
 
-    fn validate_id(value: &Option<Entry>) -> bool {
-        use Entry::StrVal;
-        match value {
-            Some(StrVal(id_str)) => match id_str.parse::<u32>() {
-                Ok(_) => id_str.chars().count() == 9,
-                Err(_) => false,
-            },
-            _ => false,
-        }
+    fn is_valid(&self, rules: &Rules) -> bool {
+        // Checks whether all required entries exist
+        rules.has_required(self.fields().iter().map(|field| field.0))
     }
 
     fn validate(&self) -> bool {
-        vec![
-            Passport::validate_limits(&self.birth_year, 1920, 2002),
-            Passport::validate_limits(&self.issue_year, 2010, 2020),
-            Passport::validate_limits(&self.exp_year, 2020, 2030),
-            Passport::validate_height(&self.height),
-            Passport::validate_hair_color(&self.hair_color),
-            Passport::validate_eye_color(&self.eye_color),
-            Passport::validate_id(&self.pid),
-        ]
-        .iter()
-        .all(|x| *x)
+        self.typed.is_some()
     }
-}
-
-impl FromStr for Passport {
-    type Err = PassParseError;
-
-    fn from_str(s: &str) -> Result<Passport, PassParseError> {
-        let pass_str: String = s.split('\n').intersperse(" ").collect();
 
-        let fields = pass_str.split_whitespace().map(|field| {
-            field
-                .split(":")
-                .collect_tuple::<(&str, &str)>()
-                .ok_or(PassParseError)
-        });
+    fn report(&self, rules: &Rules) -> Report {
+        let fields = self.fields();
+        rules.report(fields)
+    }
 
+    fn from_record(record: &RawRecord, rules: &Rules) -> Passport {
         let mut passport = Passport::new();
 
-        for field in fields {
-            let field = match field {
-                Ok(field) => field,
-                Err(e) => return Err(e),
-            };
-
-            let entry = field.1.parse::<u32>().map_or_else(
-                |_| Some(Entry::StrVal(field.1.to_string())),
-                |value| Some(Entry::IntVal(value)),
-            );
+        for (key, value) in record.pairs() {
+            let entry = Some(value.to_string());
 
-            match field.0 {
+            match key {
                 "byr" => passport.birth_year = entry,
                 "iyr" => passport.issue_year = entry,
                 "eyr" => passport.exp_year = entry,
                 "hgt" => passport.height = entry,
                 "hcl" => passport.hair_color = entry,
                 "ecl" => passport.eye_color = entry,
-                "pid" => passport.pid = Some(Entry::StrVal(field.1.to_string())),
+                "pid" => passport.pid = entry,
                 "cid" => passport.cid = entry,
-                _ => return Err(PassParseError),
+                _ => passport.others.push((key.to_string(), value.to_string())),
             }
         }
-        Ok(passport)
+
+        match ValidatedPassport::checked(rules, record) {
+            Ok(typed) => passport.typed = Some(typed),
+            Err(PassportError::Rejected) => (),
+            Err(e) => eprintln!("Warning, passport passes the rules but {}", e),
+        }
+        passport
     }
 }
 
-fn get_passports() -> Vec<Passport> {
-    let file = File::open("vickz84259.txt").expect("Unable to read file");
-    let mut reader = io::BufReader::new(file);
-
-    let mut vector: Vec<Passport> = Vec::new();
-    let mut buf_1 = String::new();
-    let mut buf_2 = String::new();
-
-    loop {
-        let no_of_bytes = reader.read_line(&mut buf_1).unwrap_or(0);
-
-        buf_2.push_str(&buf_1);
-
-        if &buf_1 == "\n" || &buf_1 == "" {
-            vector.push(buf_2.parse().unwrap());
-            buf_2.clear();
+fn get_passports(rules: &Rules, mode: Mode) -> Vec<Passport> {
+    let text = fs::read_to_string("vickz84259.txt").expect("Unable to read file");
+    let records = match parse::parse_batch(&text, rules, mode) {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("Rejected vickz84259.txt, {}", e);
+            std::process::exit(1);
         }
+    };
 
-        buf_1.clear();
-
-        if no_of_bytes == 0 {
-            break;
-        }
-    }
-    vector
+    records
+        .iter()
+        .inspect(|record| {
+            record
+                .problems
+                .iter()
+                .for_each(|p| eprintln!("Warning, {}", p))
+        })
+        .map(|record| Passport::from_record(record, rules))
+        .collect()
 }
 
-fn part_1(passports: &Vec<Passport>) {
+fn part_1(passports: &[Passport], rules: &Rules) {
     let valid_count = passports
         .iter()
-        .filter(|passport| passport.is_valid())
+        .filter(|passport| passport.is_valid(rules))
         .count();
 
     println!("Valid passports: {}", valid_count);
 }
 
-fn part_2(passports: &Vec<Passport>) {
+fn part_2(passports: &[Passport]) {
     let valid_count = passports
         .iter()
         .filter(|passport| passport.validate())
@@ -224,12 +130,51 @@ fn part_2(passports: &Vec<Passport>) {
 }
 
 fn main() {
-    let passports = get_passports();
+    let rules = Rules::load("../rules.txt").expect("Unable to read rules");
+    let mode = if std::env::args().any(|arg| arg == "--strict") {
+        Mode::Strict
+    } else {
+        Mode::Lenient
+    };
+    let passports = get_passports(&rules, mode);
 
     println!("Part 1: \n----------");
-    part_1(&passports);
+    part_1(&passports, &rules);
 
     println!("----------");
     println!("Part 2: \n----------");
     part_2(&passports);
+
+    if std::env::args().any(|arg| arg == "--report") {
+        println!("----------");
+        println!("Invalid passports: \n----------");
+        passports
+            .iter()
+            .map(|passport| passport.report(&rules))
+            .enumerate()
+            .filter(|entry| !entry.1.is_valid())
+            .for_each(|entry| println!("Passport {}:\n{}", entry.0 + 1, entry.1));
+    }
+}
+
+#[cfg(test)]
+const EXTRA_KEY: &str = "byr:1980 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn pid:087499704\n\n\
+                         byr:1980 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn pid:087499704 zip:12345\n";
+
+#[test]
+fn test_extra_key() {
+    // Tests run from the crate root rather than src.
+    let rules = Rules::load("rules.txt").unwrap();
+    let passports: Vec<Passport> = parse::parse_batch(EXTRA_KEY, &rules, Mode::Lenient)
+        .unwrap()
+        .iter()
+        .map(|record| Passport::from_record(record, &rules))
+        .collect();
+    assert!(passports.iter().all(|x| x.is_valid(&rules)));
+    assert_eq!(passports.iter().filter(|x| x.validate()).count(), 1);
+    assert_eq!(
+        passports[0].typed.as_ref().map(|x| x.height.to_string()),
+        Some("74in".to_string())
+    );
+    assert_eq!(passports[1].fields().last(), Some(&("zip", "12345")));
 }
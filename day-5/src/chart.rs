@@ -0,0 +1,166 @@
+// Who sits where: every seat on the plane, taken or not.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::{BoardingPass, Cabin};
+
+/// Why a seat has no pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vacancy {
+    /// Before the first taken seat, where the plane has no seats.
+    Front,
+    /// After the last taken seat, likewise.
+    Back,
+    /// Between two taken seats, like yours.
+    Isolated,
+    /// In the middle of the plane, next to another empty seat.
+    Run,
+}
+
+/// The passes scanned so far, by seat ID.
+#[derive(Debug, Clone)]
+pub struct SeatingChart {
+    cabin: Cabin,
+    passes: BTreeMap<usize, usize>,
+}
+
+impl SeatingChart {
+    pub fn new<I: IntoIterator<Item = BoardingPass>>(cabin: Cabin, passes: I) -> SeatingChart {
+        let mut chart = SeatingChart {
+            cabin,
+            passes: BTreeMap::new(),
+        };
+        for pass in passes {
+            chart.add(&pass);
+        }
+        chart
+    }
+
+    pub fn add(&mut self, pass: &BoardingPass) {
+        *self.passes.entry(pass.seat_id).or_insert(0) += 1;
+    }
+
+    pub fn cabin(&self) -> Cabin {
+        self.cabin
+    }
+
+    pub fn is_taken(&self, seat_id: usize) -> bool {
+        self.passes.contains_key(&seat_id)
+    }
+
+    /// Seat IDs with more than one pass, and how many passes each has.
+    pub fn duplicates(&self) -> Vec<(usize, usize)> {
+        self.passes
+            .iter()
+            .filter(|x| *x.1 > 1)
+            .map(|(&id, &count)| (id, count))
+            .collect()
+    }
+
+    /// What kind of empty seat `seat_id` is, or `None` if it is taken.
+    /// With no passes at all, every seat counts as missing from the front.
+    pub fn vacancy(&self, seat_id: usize) -> Option<Vacancy> {
+        if self.is_taken(seat_id) {
+            return None;
+        }
+        let first = self.passes.keys().next();
+        let last = self.passes.keys().next_back();
+        Some(match (first, last) {
+            (Some(&first), _) if seat_id < first => Vacancy::Front,
+            (None, _) => Vacancy::Front,
+            (_, Some(&last)) if seat_id > last => Vacancy::Back,
+            _ if self.is_taken(seat_id - 1) && self.is_taken(seat_id + 1) => Vacancy::Isolated,
+            _ => Vacancy::Run,
+        })
+    }
+
+    /// Every empty seat in ID order.
+    pub fn empty_seats(&self) -> Vec<(usize, Vacancy)> {
+        (0..self.cabin.seats())
+            .filter_map(|id| self.vacancy(id).map(|vacancy| (id, vacancy)))
+            .collect()
+    }
+
+    /// The plane seen from above, front row first. Each seat is one of:
+    ///
+    /// - `#` taken, `!` on more than one pass
+    /// - `-` missing from the front or back of the plane
+    /// - `O` an isolated empty seat, `.` any other empty seat
+    ///
+    /// Rows are numbered, and an aisle splits planes four or more seats wide.
+    pub fn to_ascii(&self) -> String {
+        let cols = self.cabin.cols();
+        let label = (self.cabin.rows() - 1).to_string().len();
+        let mut text = String::new();
+        for row in 0..self.cabin.rows() {
+            let _ = write!(text, "{:>1$} ", row, label);
+            for col in 0..cols {
+                if cols >= 4 && col == cols / 2 {
+                    text.push(' ');
+                }
+                let id = self.cabin.seat_id(row, col);
+                text.push(match (self.passes.get(&id), self.vacancy(id)) {
+                    (Some(1), _) => '#',
+                    (Some(_), _) => '!',
+                    (None, Some(Vacancy::Front)) | (None, Some(Vacancy::Back)) => '-',
+                    (None, Some(Vacancy::Isolated)) => 'O',
+                    (None, _) => '.',
+                });
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+fn example() -> SeatingChart {
+    let cabin = Cabin::new(4, 4).unwrap();
+    let taken = [5, 6, 6, 8, 9, 12];
+    SeatingChart::new(
+        cabin,
+        taken.iter().map(|&id| cabin.pass_for_id(id).unwrap()),
+    )
+}
+
+#[test]
+fn test_empty_seats() {
+    let chart = example();
+    let empty = chart.empty_seats();
+    assert_eq!(empty.len(), 11);
+    assert_eq!(
+        empty[..5].iter().map(|x| x.1).collect::<Vec<_>>(),
+        vec![Vacancy::Front; 5]
+    );
+    assert_eq!(chart.vacancy(7), Some(Vacancy::Isolated));
+    assert_eq!(chart.vacancy(10), Some(Vacancy::Run));
+    assert_eq!(chart.vacancy(11), Some(Vacancy::Run));
+    assert_eq!(chart.vacancy(13), Some(Vacancy::Back));
+    assert_eq!(chart.vacancy(6), None);
+
+    let nobody = SeatingChart::new(Cabin::default(), Vec::new());
+    assert_eq!(nobody.vacancy(500), Some(Vacancy::Front));
+}
+
+#[test]
+fn test_duplicates() {
+    let mut chart = example();
+    assert_eq!(chart.duplicates(), vec![(6, 2)]);
+    chart.add(&chart.cabin().pass_for_id(12).unwrap());
+    chart.add(&chart.cabin().pass_for_id(6).unwrap());
+    assert_eq!(chart.duplicates(), vec![(6, 3), (12, 2)]);
+}
+
+#[test]
+fn test_ascii() {
+    assert_eq!(
+        example().to_ascii(),
+        "0 -- --\n\
+         1 -# !O\n\
+         2 ## ..\n\
+         3 #- --\n"
+    );
+    let narrow = Cabin::new(2, 2).unwrap();
+    let chart = SeatingChart::new(narrow, vec![narrow.pass_for_id(1).unwrap()]);
+    assert_eq!(chart.to_ascii(), "0 -#\n1 --\n");
+}
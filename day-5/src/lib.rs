@@ -0,0 +1,437 @@
+// --- Day 5: Binary Boarding ---
+//
+// Pieces shared by the day 5 solutions.
+pub mod chart;
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The shape of the plane: a power of two rows, each a power of two
+/// columns wide. Seat IDs number the seats row by row, so a seat's ID is
+/// `row * cols + col`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cabin {
+    rows: usize,
+    cols: usize,
+}
+
+/// The puzzle's plane, 128 rows of 8.
+impl Default for Cabin {
+    fn default() -> Self {
+        Cabin { rows: 128, cols: 8 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidCabin(pub String);
+
+impl fmt::Display for InvalidCabin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid cabin {:?}, expected ROWSxCOLS in powers of two",
+            self.0
+        )
+    }
+}
+
+impl Error for InvalidCabin {}
+
+impl Cabin {
+    pub fn new(rows: usize, cols: usize) -> Result<Cabin, InvalidCabin> {
+        if !rows.is_power_of_two() || !cols.is_power_of_two() {
+            return Err(InvalidCabin(format!("{}x{}", rows, cols)));
+        }
+        Ok(Cabin { rows, cols })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn seats(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    pub fn row_bits(&self) -> usize {
+        self.rows.trailing_zeros() as usize
+    }
+
+    pub fn col_bits(&self) -> usize {
+        self.cols.trailing_zeros() as usize
+    }
+
+    /// How many letters a boarding pass has.
+    pub fn pass_len(&self) -> usize {
+        self.row_bits() + self.col_bits()
+    }
+
+    pub fn seat_id(&self, row: usize, col: usize) -> usize {
+        row * self.cols + col
+    }
+
+    /// `(row, col)` of a seat ID. IDs past the last seat give rows past
+    /// the last row.
+    pub fn seat(&self, seat_id: usize) -> (usize, usize) {
+        (seat_id / self.cols, seat_id % self.cols)
+    }
+
+    /// The pass for a seat, or `None` if the cabin has no such seat.
+    pub fn pass(&self, row: usize, col: usize) -> Option<BoardingPass> {
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+        Some(BoardingPass {
+            row,
+            col,
+            seat_id: self.seat_id(row, col),
+            cabin: *self,
+        })
+    }
+
+    pub fn pass_for_id(&self, seat_id: usize) -> Option<BoardingPass> {
+        let (row, col) = self.seat(seat_id);
+        self.pass(row, col)
+    }
+
+    pub fn encode(&self, row: usize, col: usize) -> Option<String> {
+        self.pass(row, col).map(|pass| pass.to_string())
+    }
+
+    /// Checks a pass has the right letters in the right places for this
+    /// cabin, without decoding it.
+    pub fn validate(&self, s: &str) -> Result<(), ParsePassError> {
+        let found = s.chars().count();
+        if found != self.pass_len() {
+            return Err(ParsePassError::Length {
+                expected: self.pass_len(),
+                found,
+            });
+        }
+        for (pos, c) in s.chars().enumerate() {
+            let in_row = pos < self.row_bits();
+            match c {
+                'F' | 'B' if in_row => (),
+                'L' | 'R' if !in_row => (),
+                'F' | 'B' | 'L' | 'R' => return Err(ParsePassError::Misplaced { c, pos }),
+                _ => return Err(ParsePassError::BadChar { c, pos }),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn parse(&self, s: &str) -> Result<BoardingPass, ParsePassError> {
+        self.validate(s)?;
+        let (row, col) = s.split_at(self.row_bits());
+        let number = |part: &str, one: char| {
+            part.chars()
+                .fold(0, |value, c| value << 1 | (c == one) as usize)
+        };
+        // Row and column letters can only spell seats in the cabin.
+        Ok(self
+            .pass(number(row, 'B'), number(col, 'R'))
+            .expect("decoded seat outside the cabin"))
+    }
+
+    /// Reads one pass per line, skipping blank lines. Lines that are not
+    /// valid passes are set aside so the rest can still be used.
+    pub fn parse_lines(&self, text: &str) -> (Vec<BoardingPass>, Vec<Rejected>) {
+        let mut passes = Vec::new();
+        let mut rejected = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            match self.parse(line) {
+                Ok(pass) => passes.push(pass),
+                Err(error) => rejected.push(Rejected {
+                    line: index + 1,
+                    text: line.to_string(),
+                    error,
+                }),
+            }
+        }
+        (passes, rejected)
+    }
+
+    /// Free seats whose neighbouring IDs are both taken, like yours.
+    pub fn gaps(&self, taken: &HashSet<usize>) -> Vec<usize> {
+        (1..self.seats().saturating_sub(1))
+            .filter(|id| !taken.contains(id))
+            .filter(|id| taken.contains(&(id - 1)) && taken.contains(&(id + 1)))
+            .collect()
+    }
+}
+
+/// Written as `ROWSxCOLS`, e.g. `128x8`.
+impl FromStr for Cabin {
+    type Err = InvalidCabin;
+
+    fn from_str(s: &str) -> Result<Cabin, InvalidCabin> {
+        let invalid = || InvalidCabin(s.to_string());
+        let (rows, cols) = s.split_once('x').ok_or_else(invalid)?;
+        let rows = rows.parse().map_err(|_| invalid())?;
+        let cols = cols.parse().map_err(|_| invalid())?;
+        Cabin::new(rows, cols).map_err(|_| invalid())
+    }
+}
+
+impl fmt::Display for Cabin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.rows, self.cols)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct BoardingPass {
+    pub row: usize,
+    pub col: usize,
+    pub seat_id: usize,
+    pub cabin: Cabin,
+}
+
+impl BoardingPass {
+    /// A pass on the puzzle's plane; see `Cabin::pass` for others.
+    pub fn new(row: usize, col: usize) -> Option<BoardingPass> {
+        Cabin::default().pass(row, col)
+    }
+
+    pub fn from_seat_id(seat_id: usize) -> Option<BoardingPass> {
+        Cabin::default().pass_for_id(seat_id)
+    }
+}
+
+/// The pass for a seat, e.g. `FBFBBFFRLR` for row 44, column 5, or `None`
+/// past the last row or column.
+pub fn encode(row: usize, col: usize) -> Option<String> {
+    Cabin::default().encode(row, col)
+}
+
+pub fn encode_seat_id(seat_id: usize) -> Option<String> {
+    BoardingPass::from_seat_id(seat_id).map(|pass| pass.to_string())
+}
+
+/// `bits` letters for `value`, most significant first.
+fn letters(value: usize, bits: usize, zero: char, one: char) -> impl Iterator<Item = char> {
+    (0..bits)
+        .rev()
+        .map(move |bit| if value >> bit & 1 == 1 { one } else { zero })
+}
+
+impl fmt::Display for BoardingPass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let row = letters(self.row, self.cabin.row_bits(), 'F', 'B');
+        let col = letters(self.col, self.cabin.col_bits(), 'L', 'R');
+        row.chain(col)
+            .try_for_each(|c| fmt::Write::write_char(f, c))
+    }
+}
+
+/// What is wrong with a boarding pass. Positions count letters from 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParsePassError {
+    Length {
+        expected: usize,
+        found: usize,
+    },
+    /// A letter that is not one of `FBLR`.
+    BadChar {
+        c: char,
+        pos: usize,
+    },
+    /// A row letter among the columns, or a column letter among the rows.
+    Misplaced {
+        c: char,
+        pos: usize,
+    },
+}
+
+impl fmt::Display for ParsePassError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParsePassError::Length { expected, found } => {
+                write!(f, "expected {} letters, found {}", expected, found)
+            }
+            ParsePassError::BadChar { c, pos } => {
+                write!(f, "unexpected {:?} at position {}", c, pos)
+            }
+            ParsePassError::Misplaced { c, pos } => match c {
+                'F' | 'B' => write!(
+                    f,
+                    "row letter {:?} among the columns at position {}",
+                    c, pos
+                ),
+                _ => write!(
+                    f,
+                    "column letter {:?} among the rows at position {}",
+                    c, pos
+                ),
+            },
+        }
+    }
+}
+
+impl Error for ParsePassError {}
+
+/// A line that could not be read as a boarding pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejected {
+    /// Counted from 1.
+    pub line: usize,
+    pub text: String,
+    pub error: ParsePassError,
+}
+
+impl fmt::Display for Rejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {:?}: {}", self.line, self.text, self.error)
+    }
+}
+
+/// Reads a pass for the puzzle's plane; see `Cabin::parse` for others.
+impl FromStr for BoardingPass {
+    type Err = ParsePassError;
+
+    fn from_str(s: &str) -> Result<BoardingPass, ParsePassError> {
+        Cabin::default().parse(s)
+    }
+}
+
+#[test]
+fn test_encode() {
+    assert_eq!(encode(44, 5).unwrap(), "FBFBBFFRLR");
+    assert_eq!(encode_seat_id(567).unwrap(), "BFFFBBFRRR");
+    assert_eq!(BoardingPass::new(102, 4).unwrap().to_string(), "BBFFBBFRLL");
+    assert_eq!(encode(0, 0).unwrap(), "FFFFFFFLLL");
+    assert_eq!(encode(127, 7).unwrap(), "BBBBBBBRRR");
+}
+
+#[test]
+fn test_out_of_range() {
+    assert_eq!(encode_seat_id(1023).unwrap(), "BBBBBBBRRR");
+    assert_eq!(encode_seat_id(1024), None);
+    assert_eq!(encode(128, 0), None);
+    assert_eq!(encode(0, 8), None);
+    assert_eq!(BoardingPass::from_seat_id(1024), None);
+
+    let small = Cabin::new(4, 2).unwrap();
+    assert_eq!(small.pass(3, 1).map(|x| x.seat_id), Some(7));
+    assert_eq!(small.pass(4, 0), None);
+    assert_eq!(small.pass(0, 2), None);
+    assert_eq!(small.pass(200, 0), None);
+    assert_eq!(
+        small.pass_for_id(7).map(|x| x.to_string()),
+        Some("BBR".to_string())
+    );
+    assert_eq!(small.pass_for_id(8), None);
+    assert_eq!(small.encode(4, 1), None);
+}
+
+#[test]
+fn test_round_trip() {
+    for &(rows, cols) in &[(128, 8), (4, 4), (1, 2), (256, 16)] {
+        let cabin = Cabin::new(rows, cols).unwrap();
+
+        // Every seat on the plane, both ways round.
+        for seat_id in 0..cabin.seats() {
+            let pass = cabin.pass_for_id(seat_id).unwrap();
+            assert_eq!(pass.seat_id, seat_id);
+            assert_eq!(cabin.parse(&pass.to_string()), Ok(pass.clone()));
+            assert_eq!(cabin.encode(pass.row, pass.col), Some(pass.to_string()));
+        }
+
+        // Every string of the right shape decodes and encodes back unchanged.
+        for bits in 0..cabin.seats() {
+            let s: String = letters(bits >> cabin.col_bits(), cabin.row_bits(), 'F', 'B')
+                .chain(letters(bits, cabin.col_bits(), 'L', 'R'))
+                .collect();
+            let pass = cabin.parse(&s).unwrap();
+            assert_eq!(pass.seat_id, bits);
+            assert_eq!(pass.to_string(), s);
+        }
+    }
+}
+
+#[test]
+fn test_parse_errors() {
+    let error = |s: &str| s.parse::<BoardingPass>().unwrap_err();
+    let length = |found| ParsePassError::Length {
+        expected: 10,
+        found,
+    };
+    assert_eq!(error(""), length(0));
+    assert_eq!(error("FBFBBFFRL"), length(9));
+    assert_eq!(error("FBFBBFFRLRR"), length(11));
+    assert_eq!(
+        error("FBFBBFFRLÉ"),
+        ParsePassError::BadChar { c: 'É', pos: 9 }
+    );
+    assert_eq!(
+        error("fBFBBFFRLR"),
+        ParsePassError::BadChar { c: 'f', pos: 0 }
+    );
+    assert_eq!(
+        error("FBFBBFRRLR"),
+        ParsePassError::Misplaced { c: 'R', pos: 6 }
+    );
+    assert_eq!(
+        error("FBFBBFFRBR"),
+        ParsePassError::Misplaced { c: 'B', pos: 8 }
+    );
+    assert_eq!(
+        error("FBFBBFFRBR").to_string(),
+        "row letter 'B' among the columns at position 8"
+    );
+
+    let small = Cabin::new(4, 2).unwrap();
+    assert_eq!(small.parse("BFR").map(|x| x.seat_id), Ok(5));
+    assert_eq!(
+        small.parse("FBFBBFFRLR"),
+        Err(ParsePassError::Length {
+            expected: 3,
+            found: 10
+        })
+    );
+}
+
+#[test]
+fn test_parse_lines() {
+    let text = "BFFFBBFRRR\nFFFBBBFRR\n\nBBFFBBFRLL\nBBFFBBFRLX\n";
+    let (passes, rejected) = Cabin::default().parse_lines(text);
+    assert_eq!(
+        passes.iter().map(|x| x.seat_id).collect::<Vec<_>>(),
+        vec![567, 820]
+    );
+    assert_eq!(
+        rejected.iter().map(|x| x.line).collect::<Vec<_>>(),
+        vec![2, 5]
+    );
+    assert_eq!(
+        rejected[1].to_string(),
+        "line 5: \"BBFFBBFRLX\": unexpected 'X' at position 9"
+    );
+}
+
+#[test]
+fn test_cabin() {
+    assert_eq!(Cabin::default(), "128x8".parse().unwrap());
+    assert_eq!(Cabin::default().to_string(), "128x8");
+    assert_eq!(Cabin::default().pass_len(), 10);
+    assert!(Cabin::new(100, 8).is_err());
+    assert!(Cabin::new(128, 0).is_err());
+    assert!("128by8".parse::<Cabin>().is_err());
+
+    let cabin = Cabin::new(4, 4).unwrap();
+    assert_eq!(cabin.seat_id(2, 3), 11);
+    assert_eq!(cabin.seat(11), (2, 3));
+
+    let taken: HashSet<usize> = vec![0, 1, 3, 4, 7, 9, 10, 11, 15].into_iter().collect();
+    assert_eq!(cabin.gaps(&taken), vec![2, 8]);
+}
@@ -1,108 +1,95 @@
 // --- Day 5: Binary Boarding ---
 //
 // https://adventofcode.com/2020/day/5
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-struct BoardingPass {
-    row: usize,
-    col: usize,
-    seat_id: usize,
-}
+use day_5::chart::{SeatingChart, Vacancy};
+use day_5::{BoardingPass, Cabin};
 
-impl BoardingPass {
-    fn new(row: usize, col: usize) -> BoardingPass {
-        BoardingPass {
-            row,
-            col,
-            seat_id: row * 8 + col,
+fn main() {
+    // `matt encode ID...` issues the boarding pass for each seat ID.
+    // `--cabin ROWSxCOLS` describes a different plane.
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let mut cabin = Cabin::default();
+    if let Some(at) = args.iter().position(|x| x == "--cabin") {
+        cabin = args[at + 1].parse().unwrap();
+        args.drain(at..at + 2);
+    }
+
+    if args.first().map(String::as_str) == Some("encode") {
+        for id in &args[1..] {
+            let id: usize = id.parse().expect("seat IDs are numbers");
+            match cabin.pass_for_id(id) {
+                Some(pass) => println!("{} {}", id, pass),
+                None => eprintln!("{}: no such seat on a {} plane", id, cabin),
+            }
         }
+        return;
     }
-}
 
-fn main() {
-    let passes = input("src/matt.txt").unwrap();
+    let passes = input("src/matt.txt", &cabin).unwrap();
+
+    // `matt chart` draws the plane and lists what is odd about it.
+    if args.first().map(String::as_str) == Some("chart") {
+        let chart = SeatingChart::new(cabin, passes);
+        print!("{}", chart.to_ascii());
+        for (id, count) in chart.duplicates() {
+            println!("Seat {} is on {} passes", id, count);
+        }
+        for (id, vacancy) in chart.empty_seats() {
+            if vacancy == Vacancy::Isolated || vacancy == Vacancy::Run {
+                println!(
+                    "Seat {} ({}) is empty: {:?}",
+                    id,
+                    cabin.pass_for_id(id).unwrap(),
+                    vacancy
+                );
+            }
+        }
+        return;
+    }
 
     println!("Part One: {} ", part_one(&passes));
-    println!("Part Two: {} ", part_two(&passes));
+    println!("Part Two: {} ", part_two(&passes, &cabin));
 }
 
-fn part_one(xs: &Vec<BoardingPass>) -> usize {
+fn part_one(xs: &[BoardingPass]) -> usize {
     xs.iter().map(|x| x.seat_id).max().unwrap()
 }
-fn part_two(xs: &Vec<BoardingPass>) -> usize {
-    let mut ids: Vec<_> = xs.iter().map(|x| x.seat_id).collect();
-    ids.sort();
-    let min = *ids.iter().min().unwrap();
-    let max = *ids.iter().max().unwrap();
-    for idx in min..=max {
-        if !ids.contains(&idx) {
-            return idx;
-        }
-    }
-    0
-}
-fn parse_boarding_pass(s: &str) -> BoardingPass {
-    let (row, col) = s.split_at(s.len() - 3);
-    let row_binstr = row
-        .chars()
-        .map(|x| match x {
-            'F' => '0',
-            'B' => '1',
-            _ => unreachable!(),
-        })
-        .collect::<String>();
-    let col_binstr = col
-        .chars()
-        .map(|x| match x {
-            'R' => '1',
-            'L' => '0',
-            _ => unreachable!(),
-        })
-        .collect::<String>();
-    let row = usize::from_str_radix(&row_binstr, 2).unwrap();
-    let col = usize::from_str_radix(&col_binstr, 2).unwrap();
-    BoardingPass::new(row, col)
+fn part_two(xs: &[BoardingPass], cabin: &Cabin) -> usize {
+    let ids: HashSet<_> = xs.iter().map(|x| x.seat_id).collect();
+    cabin.gaps(&ids).first().copied().unwrap_or(0)
 }
-fn input(fname: &str) -> io::Result<Vec<BoardingPass>> {
-    let file = File::open(fname)?;
-    let buf = io::BufReader::new(file);
-    Ok(buf
-        .lines()
-        .map(|line| parse_boarding_pass(&line.unwrap()))
-        .collect())
+fn input(fname: &str, cabin: &Cabin) -> io::Result<Vec<BoardingPass>> {
+    let (passes, rejected) = cabin.parse_lines(&fs::read_to_string(fname)?);
+    for x in rejected {
+        eprintln!("{}: skipping {}", fname, x);
+    }
+    Ok(passes)
 }
 
 #[test]
 fn test_parsing() {
-    let tests = vec![
-        (
-            "BFFFBBFRRR",
-            BoardingPass {
-                row: 70,
-                col: 7,
-                seat_id: 567,
-            },
-        ),
-        (
-            "FFFBBBFRRR",
-            BoardingPass {
-                row: 14,
-                col: 7,
-                seat_id: 119,
-            },
-        ),
-        (
-            "BBFFBBFRLL",
-            BoardingPass {
-                row: 102,
-                col: 4,
-                seat_id: 820,
-            },
-        ),
+    let tests = [
+        ("BFFFBBFRRR", (70, 7, 567)),
+        ("FFFBBBFRRR", (14, 7, 119)),
+        ("BBFFBBFRLL", (102, 4, 820)),
     ];
-    for (input, pass) in tests.iter() {
-        assert_eq!(pass, &parse_boarding_pass(input));
+    for (input, expected) in tests.iter() {
+        let pass = Cabin::default().parse(input).unwrap();
+        assert_eq!(expected, &(pass.row, pass.col, pass.seat_id));
     }
 }
+
+#[test]
+fn test_small_cabin() {
+    let cabin: Cabin = "8x4".parse().unwrap();
+    let passes: Vec<_> = ["FFFLL", "FFFLR", "FFFRR", "BBBRL"]
+        .iter()
+        .map(|s| cabin.parse(s).unwrap())
+        .collect();
+    assert_eq!(part_one(&passes), 30);
+    assert_eq!(part_two(&passes, &cabin), 2);
+}
@@ -1,38 +1,41 @@
 use std::collections::HashSet;
+use std::fmt;
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::str::FromStr;
 
 use itertools::Itertools;
 
+use day_5::{Cabin, ParsePassError};
+
 struct BoardingPass {
     row_range: (u32, u32),
     col_range: (u32, u32),
+    cabin: Cabin,
 }
 
-#[derive(Debug)]
-struct PassParseError;
-
-impl Default for BoardingPass {
-    fn default() -> Self {
+impl BoardingPass {
+    fn new(cabin: Cabin) -> Self {
         BoardingPass {
-            row_range: (0, 127),
-            col_range: (0, 7),
+            row_range: (0, cabin.rows() as u32 - 1),
+            col_range: (0, cabin.cols() as u32 - 1),
+            cabin,
         }
     }
-}
 
-impl BoardingPass {
-    fn new() -> Self {
-        Default::default()
-    }
+    fn parse(s: &str, cabin: Cabin) -> Result<BoardingPass, ParsePassError> {
+        // Catches wrong lengths and letters out of place before bisecting.
+        cabin.validate(s)?;
+        let mut pass = BoardingPass::new(cabin);
 
-    fn get_seat_id(row: u32, column: u32) -> u32 {
-        (row * 8) + column
+        for (pos, character) in s.chars().enumerate() {
+            pass.partition(pos, character)?;
+        }
+        Ok(pass)
     }
 
     fn set_range(range: &mut (u32, u32), lower: bool) {
-        let new_high = (range.1 + range.0 + 1) / 2;
+        let new_high = (range.1 + range.0).div_ceil(2);
         *range = if lower {
             (range.0, new_high - 1)
         } else {
@@ -40,69 +43,89 @@ impl BoardingPass {
         };
     }
 
-    fn partition(&mut self, character: &char) -> Result<(), PassParseError> {
-        match character {
+    fn partition(&mut self, pos: usize, c: char) -> Result<(), ParsePassError> {
+        match c {
             'F' => BoardingPass::set_range(&mut self.row_range, true),
             'B' => BoardingPass::set_range(&mut self.row_range, false),
             'L' => BoardingPass::set_range(&mut self.col_range, true),
             'R' => BoardingPass::set_range(&mut self.col_range, false),
-            _ => return Err(PassParseError),
+            _ => return Err(ParsePassError::BadChar { c, pos }),
         };
         Ok(())
     }
 
-    fn seat_id(&self) -> u32 {
-        BoardingPass::get_seat_id(self.row_range.0, self.col_range.0)
+    fn seat_id(&self) -> usize {
+        self.cabin
+            .seat_id(self.row_range.0 as usize, self.col_range.0 as usize)
     }
 }
 
-impl FromStr for BoardingPass {
-    type Err = PassParseError;
-
-    fn from_str(s: &str) -> Result<BoardingPass, PassParseError> {
-        let mut pass = BoardingPass::new();
+impl fmt::Display for BoardingPass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Bisecting never leaves the cabin.
+        let pass = self
+            .cabin
+            .encode(self.row_range.0 as usize, self.col_range.0 as usize)
+            .expect("seat outside the cabin");
+        f.write_str(&pass)
+    }
+}
 
-        for character in s.chars() {
-            pass.partition(&character)?;
-        }
+impl FromStr for BoardingPass {
+    type Err = ParsePassError;
 
-        if pass.row_range.0 != pass.row_range.1 || pass.col_range.0 != pass.col_range.1 {
-            return Err(PassParseError);
-        }
-        Ok(pass)
+    fn from_str(s: &str) -> Result<BoardingPass, ParsePassError> {
+        BoardingPass::parse(s, Cabin::default())
     }
 }
 
-fn get_passes() -> Vec<BoardingPass> {
+fn get_passes(cabin: Cabin) -> Vec<BoardingPass> {
     let file = File::open("vickz84259.txt").expect("Unable to read file");
-    let lines = io::BufReader::new(file).lines().filter_map(Result::ok);
-
-    lines.map(|string| string.parse().unwrap()).collect()
+    let lines = io::BufReader::new(file).lines().map_while(Result::ok);
+
+    lines
+        .enumerate()
+        .filter_map(
+            |(index, string)| match BoardingPass::parse(&string, cabin) {
+                Ok(pass) => Some(pass),
+                Err(e) => {
+                    eprintln!("Skipping line {}, {}", index + 1, e);
+                    None
+                }
+            },
+        )
+        .collect()
 }
 
-fn part_1(seat_ids: &HashSet<u32>) {
-    let highest_seat_id = seat_ids.iter().max().unwrap_or(&0u32);
+fn part_1(seat_ids: &HashSet<usize>) {
+    let highest_seat_id = seat_ids.iter().max().unwrap_or(&0);
     println!("Highest Seat Id: {}", highest_seat_id);
 }
 
-fn part_2(seat_ids: &HashSet<u32>) {
-    let seat_id = (9u32..119u32)
-        .cartesian_product(0u32..7u32)
-        .map(|product| BoardingPass::get_seat_id(product.0, product.1))
-        .filter(|seat_id| !seat_ids.contains(&seat_id))
-        .exactly_one()
-        .unwrap();
+fn part_2(seat_ids: &HashSet<usize>, cabin: Cabin) {
+    let gaps = cabin.gaps(seat_ids);
 
-    println!("Seat id: {}", seat_id);
+    match gaps.as_slice() {
+        [] => println!("No empty seat between taken ones"),
+        [seat_id] => println!("Seat id: {}", seat_id),
+        _ => println!("Seat ids: {}", gaps.iter().join(", ")),
+    }
 }
 
 fn main() {
-    let seat_ids: HashSet<u32> = get_passes().iter().map(|pass| pass.seat_id()).collect();
+    let cabin = match std::env::args().nth(1) {
+        Some(cabin) => cabin.parse().expect("Invalid cabin"),
+        None => Cabin::default(),
+    };
+    let seat_ids: HashSet<usize> = get_passes(cabin)
+        .iter()
+        .map(|pass| pass.seat_id())
+        .collect();
 
     println!("Part 1: \n----------");
     part_1(&seat_ids);
 
     println!("----------");
     println!("Part 2: \n----------");
-    part_2(&seat_ids);
+    part_2(&seat_ids, cabin);
 }
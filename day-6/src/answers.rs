@@ -0,0 +1,158 @@
+// A set of questions answered "yes", as bits where it can be.
+use std::collections::BTreeSet;
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitOr};
+
+/// Questions `a` to `z` live in a `u32`, bit 0 for `a`, so union and
+/// intersection are single bit operations. Any other question moves the
+/// set to a `BTreeSet`; results that fit back in the bitmask move back.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AnswerSet {
+    Letters(u32),
+    Chars(BTreeSet<char>),
+}
+
+fn bit(c: char) -> Option<u32> {
+    if c.is_ascii_lowercase() {
+        Some(1 << (c as u32 - 'a' as u32))
+    } else {
+        None
+    }
+}
+
+fn letters(mask: u32) -> impl Iterator<Item = char> {
+    ('a'..='z').filter(move |&c| mask & bit(c).unwrap() != 0)
+}
+
+impl Default for AnswerSet {
+    fn default() -> Self {
+        AnswerSet::Letters(0)
+    }
+}
+
+impl AnswerSet {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn insert(&mut self, c: char) {
+        match (&mut *self, bit(c)) {
+            (AnswerSet::Letters(mask), Some(bit)) => *mask |= bit,
+            (AnswerSet::Letters(mask), None) => {
+                let mut chars: BTreeSet<char> = letters(*mask).collect();
+                chars.insert(c);
+                *self = AnswerSet::Chars(chars);
+            }
+            (AnswerSet::Chars(chars), _) => {
+                chars.insert(c);
+            }
+        }
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        match (self, bit(c)) {
+            (AnswerSet::Letters(mask), Some(bit)) => mask & bit != 0,
+            (AnswerSet::Letters(_), None) => false,
+            (AnswerSet::Chars(chars), _) => chars.contains(&c),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            AnswerSet::Letters(mask) => mask.count_ones() as usize,
+            AnswerSet::Chars(chars) => chars.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The questions in order.
+    pub fn chars(&self) -> Vec<char> {
+        match self {
+            AnswerSet::Letters(mask) => letters(*mask).collect(),
+            AnswerSet::Chars(chars) => chars.iter().copied().collect(),
+        }
+    }
+
+    pub fn union(&self, other: &AnswerSet) -> AnswerSet {
+        match (self, other) {
+            (AnswerSet::Letters(a), AnswerSet::Letters(b)) => AnswerSet::Letters(a | b),
+            _ => self.chars().into_iter().chain(other.chars()).collect(),
+        }
+    }
+
+    pub fn intersection(&self, other: &AnswerSet) -> AnswerSet {
+        match (self, other) {
+            (AnswerSet::Letters(a), AnswerSet::Letters(b)) => AnswerSet::Letters(a & b),
+            _ => self
+                .chars()
+                .into_iter()
+                .filter(|&c| other.contains(c))
+                .collect(),
+        }
+    }
+}
+
+impl FromIterator<char> for AnswerSet {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut set = AnswerSet::new();
+        for c in iter {
+            set.insert(c);
+        }
+        set
+    }
+}
+
+impl From<&str> for AnswerSet {
+    fn from(answers: &str) -> Self {
+        answers.chars().collect()
+    }
+}
+
+impl BitOr for &AnswerSet {
+    type Output = AnswerSet;
+
+    fn bitor(self, other: &AnswerSet) -> AnswerSet {
+        self.union(other)
+    }
+}
+
+impl BitAnd for &AnswerSet {
+    type Output = AnswerSet;
+
+    fn bitand(self, other: &AnswerSet) -> AnswerSet {
+        self.intersection(other)
+    }
+}
+
+#[test]
+fn test_letters() {
+    let ab = AnswerSet::from("abz");
+    let bc = AnswerSet::from("bcz");
+    assert_eq!(ab, AnswerSet::Letters(0b10_0000_0000_0000_0000_0000_0011));
+    assert_eq!((&ab | &bc).chars(), vec!['a', 'b', 'c', 'z']);
+    assert_eq!((&ab & &bc).chars(), vec!['b', 'z']);
+    assert_eq!((&ab & &bc).len(), 2);
+    assert!(ab.contains('z'));
+    assert!(!ab.contains('c'));
+    assert!(!ab.contains('É'));
+    assert!(AnswerSet::new().is_empty());
+}
+
+#[test]
+fn test_other_alphabets() {
+    let greek = AnswerSet::from("αβa");
+    assert!(matches!(greek, AnswerSet::Chars(_)));
+    assert_eq!(greek.len(), 3);
+    assert!(greek.contains('β'));
+
+    let mixed = &greek | &AnswerSet::from("bA");
+    assert_eq!(mixed.chars(), vec!['A', 'a', 'b', 'α', 'β']);
+
+    // Only letters left, so back to the bitmask.
+    let common = &greek & &AnswerSet::from("abc");
+    assert_eq!(common, AnswerSet::from("a"));
+    assert_eq!(&AnswerSet::from("1a") & &AnswerSet::from("a1"), "1a".into());
+}
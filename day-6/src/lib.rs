@@ -0,0 +1,221 @@
+// --- Day 6: Custom Customs ---
+//
+// Pieces shared by the day 6 solutions.
+pub mod answers;
+pub mod stats;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::answers::AnswerSet;
+
+/// One group's answers: how many people are in it, how many of them
+/// answered "yes" to each question, and what each person answered.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Group {
+    pub number: u32,
+    pub questions: HashMap<char, u32>,
+    pub people: Vec<AnswerSet>,
+}
+
+impl Group {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds one person, given the questions they answered "yes" to.
+    pub fn add_person(&mut self, answers: &str) {
+        self.number += 1;
+        let answers = AnswerSet::from(answers);
+        for c in answers.chars() {
+            *self.questions.entry(c).or_insert(0) += 1;
+        }
+        self.people.push(answers);
+    }
+
+    /// Questions anyone in the group answered "yes" to.
+    pub fn anyone(&self) -> AnswerSet {
+        self.people.iter().fold(AnswerSet::new(), |acc, x| &acc | x)
+    }
+
+    /// Questions everyone in the group answered "yes" to.
+    pub fn everyone(&self) -> AnswerSet {
+        let mut people = self.people.iter();
+        match people.next() {
+            Some(first) => people.fold(first.clone(), |acc, x| &acc & x),
+            None => AnswerSet::new(),
+        }
+    }
+
+    pub fn from_people<'a, I: IntoIterator<Item = &'a str>>(people: I) -> Group {
+        let mut group = Group::new();
+        for person in people {
+            group.add_person(person);
+        }
+        group
+    }
+
+    /// The questions that meet `quorum`, in alphabetical order.
+    pub fn questions_where(&self, quorum: Quorum) -> Vec<char> {
+        let mut questions: Vec<char> = self
+            .questions
+            .iter()
+            .filter(|entry| quorum.is_met(*entry.1, self.number))
+            .map(|entry| *entry.0)
+            .collect();
+        questions.sort_unstable();
+        questions
+    }
+
+    pub fn count(&self, quorum: Quorum) -> usize {
+        self.questions
+            .values()
+            .filter(|&&yes| quorum.is_met(yes, self.number))
+            .count()
+    }
+}
+
+/// One line per person, as in the puzzle input.
+impl FromStr for Group {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Group, Self::Err> {
+        Ok(Group::from_people(s.split_whitespace()))
+    }
+}
+
+/// Splits a whole input into groups at blank lines.
+pub fn parse_groups(text: &str) -> Vec<Group> {
+    let mut groups = Vec::new();
+    let mut group: Option<Group> = None;
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            groups.extend(group.take());
+        } else {
+            group.get_or_insert_with(Group::new).add_person(line);
+        }
+    }
+    groups.extend(group);
+    groups
+}
+
+/// How many people in a group must have answered "yes" to a question for
+/// it to count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quorum {
+    /// At least this many people.
+    AtLeast(u32),
+    /// At least this percentage of the group.
+    Percent(u32),
+    /// Exactly one person.
+    ExactlyOne,
+}
+
+impl Quorum {
+    /// Anyone in the group, as in part one.
+    pub const ANYONE: Quorum = Quorum::AtLeast(1);
+    /// Everyone in the group, as in part two.
+    pub const EVERYONE: Quorum = Quorum::Percent(100);
+
+    /// Whether `yes` people out of a group of `people` is enough.
+    pub fn is_met(&self, yes: u32, people: u32) -> bool {
+        match *self {
+            Quorum::AtLeast(k) => yes >= k,
+            Quorum::Percent(p) => yes as u64 * 100 >= p as u64 * people as u64,
+            Quorum::ExactlyOne => yes == 1,
+        }
+    }
+}
+
+/// Written as `any`, `all`, `at-least:K`, `percent:P` or `exactly-one`.
+impl FromStr for Quorum {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Quorum, String> {
+        let number = |n: &str| {
+            n.parse()
+                .map_err(|_| format!("expected a number in {:?}", s))
+        };
+        match s.split_once(':') {
+            None if s == "any" => Ok(Quorum::ANYONE),
+            None if s == "all" => Ok(Quorum::EVERYONE),
+            None if s == "exactly-one" => Ok(Quorum::ExactlyOne),
+            Some(("at-least", k)) => number(k).map(Quorum::AtLeast),
+            Some(("percent", p)) => number(p).map(Quorum::Percent),
+            _ => Err(format!(
+                "unknown query {:?}, expected any, all, at-least:K, percent:P or exactly-one",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Quorum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Quorum::AtLeast(k) => write!(f, "at-least:{}", k),
+            Quorum::Percent(p) => write!(f, "percent:{}", p),
+            Quorum::ExactlyOne => f.write_str("exactly-one"),
+        }
+    }
+}
+
+/// The sum of `Group::count` over every group.
+pub fn total(groups: &[Group], quorum: Quorum) -> usize {
+    groups.iter().map(|group| group.count(quorum)).sum()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb\n";
+
+#[test]
+fn test_parse_groups() {
+    let groups = parse_groups(EXAMPLE);
+    assert_eq!(groups.len(), 5);
+    assert_eq!(
+        groups.iter().map(|x| x.number).collect::<Vec<_>>(),
+        vec![1, 3, 2, 4, 1]
+    );
+    assert_eq!(groups[2], "ab\nac".parse().unwrap());
+    assert_eq!(groups[2].questions[&'a'], 2);
+    assert_eq!(groups[2].people[1], AnswerSet::from("ca"));
+    assert_eq!(parse_groups("\n\nab\n\n\n\nc\n\n").len(), 2);
+}
+
+#[test]
+fn test_quorum() {
+    let groups = parse_groups(EXAMPLE);
+    assert_eq!(total(&groups, Quorum::ANYONE), 11);
+    assert_eq!(total(&groups, Quorum::EVERYONE), 6);
+    assert_eq!(total(&groups, Quorum::AtLeast(2)), 2);
+    assert_eq!(total(&groups, Quorum::Percent(50)), 8);
+    assert_eq!(total(&groups, Quorum::ExactlyOne), 9);
+    assert_eq!(total(&groups, Quorum::AtLeast(0)), 11);
+    assert_eq!(total(&groups, Quorum::Percent(0)), 11);
+
+    for group in &groups {
+        assert_eq!(group.anyone().len(), group.count(Quorum::ANYONE));
+        assert_eq!(group.everyone().len(), group.count(Quorum::EVERYONE));
+    }
+    assert!(Group::new().everyone().is_empty());
+
+    assert_eq!(groups[2].questions_where(Quorum::EVERYONE), vec!['a']);
+    assert_eq!(
+        groups[2].questions_where(Quorum::ExactlyOne),
+        vec!['b', 'c']
+    );
+    assert_eq!(groups[1].count(Quorum::Percent(34)), 0);
+    assert_eq!(groups[1].count(Quorum::Percent(33)), 3);
+}
+
+#[test]
+fn test_parse_quorum() {
+    for s in &["at-least:3", "percent:75", "exactly-one"] {
+        assert_eq!(s.parse::<Quorum>().unwrap().to_string(), *s);
+    }
+    assert_eq!("any".parse(), Ok(Quorum::ANYONE));
+    assert_eq!("all".parse(), Ok(Quorum::EVERYONE));
+    assert!("at-least:x".parse::<Quorum>().is_err());
+    assert!("most".parse::<Quorum>().is_err());
+}
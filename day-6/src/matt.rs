@@ -4,52 +4,94 @@
 use std::collections::HashSet;
 use std::fs::File;
 use std::io::{self, BufRead};
+use std::time::Instant;
 
+use day_6::{Group, Quorum};
+
+fn sum_answers(v: &[Group], criteria: Quorum) -> usize {
+    v.iter().map(|g| g.count(criteria)).sum()
+}
+fn part_one(v: &[Group]) -> usize {
+    v.iter().map(|g| g.anyone().len()).sum()
+}
+fn part_two(v: &[Group]) -> usize {
+    v.iter().map(|g| g.everyone().len()).sum()
+}
+
+// The `HashSet` version the bitsets replaced, kept to benchmark against.
 type Answers = HashSet<char>;
 type GroupAnswers = Vec<Answers>;
-enum AnswersType {
-    Intersection,
-    Union,
+fn hashset_answers(v: &[Group]) -> Vec<GroupAnswers> {
+    v.iter()
+        .map(|g| {
+            g.people
+                .iter()
+                .map(|p| p.chars().into_iter().collect())
+                .collect()
+        })
+        .collect()
 }
-
-fn count_common_answers(mut group: GroupAnswers, criteria: AnswersType) -> usize {
-    match criteria {
-        AnswersType::Union => group
-            .into_iter()
-            .fold(HashSet::new(), |acc, x| acc.union(&x).cloned().collect())
-            .len(),
-        AnswersType::Intersection => {
-            let first = group.pop().unwrap();
-            group
-                .into_iter()
-                .fold(first, |acc, x| acc.intersection(&x).cloned().collect())
+fn hashset_union(v: &[GroupAnswers]) -> usize {
+    v.iter()
+        .map(|g| {
+            g.iter()
+                .cloned()
+                .fold(HashSet::new(), |acc, x| acc.union(&x).cloned().collect())
                 .len()
-        }
-    }
-}
-fn part_one(v: &Vec<GroupAnswers>) -> usize {
-    v.into_iter()
-        .map(|g| count_common_answers(g.to_vec(), AnswersType::Union))
+        })
         .sum()
 }
-fn part_two(v: &Vec<GroupAnswers>) -> usize {
-    v.into_iter()
-        .map(|g| count_common_answers(g.to_vec(), AnswersType::Intersection))
+fn hashset_intersection(v: &[GroupAnswers]) -> usize {
+    v.iter()
+        .map(|g| {
+            g.iter()
+                .cloned()
+                .reduce(|acc, x| acc.intersection(&x).cloned().collect())
+                .unwrap_or_default()
+                .len()
+        })
         .sum()
 }
+fn bench(groups: &[Group], rounds: u32) {
+    let sets = hashset_answers(groups);
+    let time = |name: &str, f: &dyn Fn() -> usize| {
+        let start = Instant::now();
+        let mut answer = 0;
+        for _ in 0..rounds {
+            answer = f();
+        }
+        println!("{:<20} {:>6} {:?}", name, answer, start.elapsed() / rounds);
+    };
+    time("HashSet union", &|| hashset_union(&sets));
+    time("AnswerSet union", &|| part_one(groups));
+    time("HashSet intersect", &|| hashset_intersection(&sets));
+    time("AnswerSet intersect", &|| part_two(groups));
+}
 fn main() {
     let groups = load_input("src/matt.txt").unwrap();
 
+    // `matt bench [ROUNDS]` times both parts against the old `HashSet`s.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("bench") {
+        bench(&groups, args.get(1).map_or(100, |x| x.parse().unwrap()));
+        return;
+    }
+
+    // `matt QUERY`, e.g. `matt at-least:2`, sums any other quorum.
+    if let Some(query) = args.first() {
+        let quorum: Quorum = query.parse().unwrap();
+        println!("{}: {} ", quorum, sum_answers(&groups, quorum));
+        return;
+    }
+
     println!("Part One: {} ", part_one(&groups));
     println!("Part Two: {} ", part_two(&groups));
 }
 
-fn vec_to_group(v: Vec<String>) -> GroupAnswers {
-    v.into_iter()
-        .map(|line| line.chars().collect::<Answers>())
-        .collect()
+fn vec_to_group(v: &[String]) -> Group {
+    Group::from_people(v.iter().map(String::as_str))
 }
-fn load_input(fname: &str) -> io::Result<Vec<GroupAnswers>> {
+fn load_input(fname: &str) -> io::Result<Vec<Group>> {
     let file = File::open(fname)?;
     let buf = io::BufReader::new(file);
     let mut groups = vec![];
@@ -57,18 +99,34 @@ fn load_input(fname: &str) -> io::Result<Vec<GroupAnswers>> {
     for line in buf.lines() {
         let line = line?;
         if line.is_empty() {
-            groups.push(vec_to_group(group.clone()));
+            groups.push(vec_to_group(&group));
             group.clear();
         } else {
             group.push(line)
         }
     }
-    groups.push(vec_to_group(group.clone()));
+    groups.push(vec_to_group(&group));
     Ok(groups)
 }
 
 #[test]
 fn test_parsing() {
     let groups = load_input("src/matt_test.txt").unwrap();
-    assert_eq!(11, part_one(&groups))
+    assert_eq!(11, part_one(&groups));
+    assert_eq!(6, part_two(&groups));
+    assert_eq!(9, sum_answers(&groups, Quorum::ExactlyOne));
+
+    let sets = hashset_answers(&groups);
+    assert_eq!(11, hashset_union(&sets));
+    assert_eq!(6, hashset_intersection(&sets));
+}
+
+#[test]
+fn test_empty_group() {
+    let groups = vec![Group::from_people(Vec::new())];
+    let sets = hashset_answers(&groups);
+    assert_eq!(0, hashset_intersection(&sets));
+    assert_eq!(0, hashset_union(&sets));
+    assert_eq!(0, part_two(&groups));
+    assert_eq!(0, part_one(&groups));
 }
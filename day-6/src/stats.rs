@@ -0,0 +1,273 @@
+// Survey-wide statistics: which questions were popular, and with whom.
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+
+use crate::{Group, Quorum};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuestionStats {
+    pub question: char,
+    /// Groups where anyone answered "yes".
+    pub groups: usize,
+    /// People who answered "yes", across all groups.
+    pub people: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupStats {
+    /// Counted from 1, in input order.
+    pub group: usize,
+    pub people: u32,
+    pub anyone: usize,
+    pub everyone: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Survey {
+    pub people: u32,
+    /// Every question anyone answered, in alphabetical order.
+    pub questions: Vec<QuestionStats>,
+    /// How many groups have each number of people.
+    pub sizes: BTreeMap<u32, usize>,
+    pub groups: Vec<GroupStats>,
+}
+
+impl Survey {
+    pub fn new(groups: &[Group]) -> Survey {
+        let mut questions: BTreeMap<char, QuestionStats> = BTreeMap::new();
+        let mut sizes = BTreeMap::new();
+        for group in groups {
+            *sizes.entry(group.number).or_insert(0) += 1;
+            for (&question, &people) in &group.questions {
+                let stats = questions.entry(question).or_insert(QuestionStats {
+                    question,
+                    groups: 0,
+                    people: 0,
+                });
+                stats.groups += 1;
+                stats.people += people;
+            }
+        }
+
+        Survey {
+            people: groups.iter().map(|group| group.number).sum(),
+            questions: questions.into_values().collect(),
+            sizes,
+            groups: groups
+                .iter()
+                .enumerate()
+                .map(|(index, group)| GroupStats {
+                    group: index + 1,
+                    people: group.number,
+                    anyone: group.count(Quorum::ANYONE),
+                    everyone: group.count(Quorum::EVERYONE),
+                })
+                .collect(),
+        }
+    }
+
+    fn popular_by<F: Fn(u32, u32) -> bool>(&self, better: F) -> Vec<char> {
+        let best = self
+            .questions
+            .iter()
+            .map(|x| x.people)
+            .reduce(|a, b| if better(b, a) { b } else { a });
+        self.questions
+            .iter()
+            .filter(|x| Some(x.people) == best)
+            .map(|x| x.question)
+            .collect()
+    }
+
+    /// The questions the most people answered "yes" to, ties included.
+    pub fn most_popular(&self) -> Vec<char> {
+        self.popular_by(|a, b| a > b)
+    }
+
+    /// The questions the fewest people answered "yes" to, ties included.
+    pub fn least_popular(&self) -> Vec<char> {
+        self.popular_by(|a, b| a < b)
+    }
+
+    pub fn questions_csv(&self) -> String {
+        let mut csv = String::from("question,groups,people\n");
+        for x in &self.questions {
+            let _ = writeln!(csv, "{},{},{}", x.question, x.groups, x.people);
+        }
+        csv
+    }
+
+    pub fn groups_csv(&self) -> String {
+        let mut csv = String::from("group,people,anyone,everyone\n");
+        for x in &self.groups {
+            let _ = writeln!(csv, "{},{},{},{}", x.group, x.people, x.anyone, x.everyone);
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        let chars = |chars: Vec<char>| -> String {
+            let quoted: Vec<String> = chars.iter().map(|c| quote(*c)).collect();
+            quoted.join(", ")
+        };
+        let mut json = String::from("{\n");
+        let _ = writeln!(json, "  \"groups\": {},", self.groups.len());
+        let _ = writeln!(json, "  \"people\": {},", self.people);
+        let _ = writeln!(
+            json,
+            "  \"most_popular\": [{}],",
+            chars(self.most_popular())
+        );
+        let _ = writeln!(
+            json,
+            "  \"least_popular\": [{}],",
+            chars(self.least_popular())
+        );
+
+        let sizes: Vec<String> = self
+            .sizes
+            .iter()
+            .map(|(size, count)| format!("\"{}\": {}", size, count))
+            .collect();
+        let _ = writeln!(json, "  \"sizes\": {{{}}},", sizes.join(", "));
+
+        let questions: Vec<String> = self
+            .questions
+            .iter()
+            .map(|x| {
+                format!(
+                    "    {{\"question\": {}, \"groups\": {}, \"people\": {}}}",
+                    quote(x.question),
+                    x.groups,
+                    x.people
+                )
+            })
+            .collect();
+        let _ = writeln!(json, "  \"questions\": [\n{}\n  ],", questions.join(",\n"));
+
+        let groups: Vec<String> = self
+            .groups
+            .iter()
+            .map(|x| {
+                format!(
+                    "    {{\"group\": {}, \"people\": {}, \"anyone\": {}, \"everyone\": {}}}",
+                    x.group, x.people, x.anyone, x.everyone
+                )
+            })
+            .collect();
+        let _ = writeln!(json, "  \"per_group\": [\n{}\n  ]", groups.join(",\n"));
+        json.push_str("}\n");
+        json
+    }
+}
+
+/// A JSON string holding just `c`.
+fn quote(c: char) -> String {
+    match c {
+        '"' => String::from("\"\\\"\""),
+        '\\' => String::from("\"\\\\\""),
+        c if (c as u32) < 0x20 => format!("\"\\u{:04x}\"", c as u32),
+        c => format!("\"{}\"", c),
+    }
+}
+
+/// A summary for the terminal; the per-group breakdown is in the exports.
+impl fmt::Display for Survey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |chars: Vec<char>| chars.into_iter().collect::<String>();
+        writeln!(f, "{} people in {} groups", self.people, self.groups.len())?;
+        writeln!(f, "Most popular: {}", list(self.most_popular()))?;
+        writeln!(f, "Least popular: {}", list(self.least_popular()))?;
+        writeln!(f, "\nQuestion  Groups  People")?;
+        for x in &self.questions {
+            writeln!(f, "{:<8}  {:>6}  {:>6}", x.question, x.groups, x.people)?;
+        }
+        writeln!(f, "\nGroup size  Groups")?;
+        for (size, count) in &self.sizes {
+            writeln!(f, "{:>10}  {:>6}", size, count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn example() -> Survey {
+    Survey::new(&crate::parse_groups(
+        "abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb\n",
+    ))
+}
+
+#[test]
+fn test_survey() {
+    let survey = example();
+    assert_eq!(survey.people, 11);
+    assert_eq!(
+        survey.questions,
+        vec![
+            QuestionStats {
+                question: 'a',
+                groups: 4,
+                people: 8
+            },
+            QuestionStats {
+                question: 'b',
+                groups: 4,
+                people: 4
+            },
+            QuestionStats {
+                question: 'c',
+                groups: 3,
+                people: 3
+            },
+        ]
+    );
+    assert_eq!(survey.most_popular(), vec!['a']);
+    assert_eq!(survey.least_popular(), vec!['c']);
+    assert_eq!(
+        survey.sizes.into_iter().collect::<Vec<_>>(),
+        vec![(1, 2), (2, 1), (3, 1), (4, 1)]
+    );
+    assert_eq!(
+        survey.groups[2],
+        GroupStats {
+            group: 3,
+            people: 2,
+            anyone: 3,
+            everyone: 1
+        }
+    );
+
+    let tie = Survey::new(&crate::parse_groups("ab\nba\n"));
+    assert_eq!(tie.most_popular(), vec!['a', 'b']);
+    assert_eq!(tie.least_popular(), vec!['a', 'b']);
+    assert!(Survey::new(&[]).most_popular().is_empty());
+}
+
+#[test]
+fn test_exports() {
+    let survey = example();
+    assert_eq!(
+        survey.questions_csv(),
+        "question,groups,people\na,4,8\nb,4,4\nc,3,3\n"
+    );
+    assert_eq!(survey.groups_csv().lines().nth(4), Some("4,4,1,1"));
+    let json = survey.to_json();
+    assert!(json.contains("\"most_popular\": [\"a\"],\n"));
+    assert!(json.contains("\"sizes\": {\"1\": 2, \"2\": 1, \"3\": 1, \"4\": 1},\n"));
+    assert!(json
+        .contains("    {\"group\": 5, \"people\": 1, \"anyone\": 1, \"everyone\": 1}\n  ]\n}\n"));
+    assert!(survey
+        .to_string()
+        .starts_with("11 people in 5 groups\nMost popular: a\n"));
+}
+
+#[test]
+fn test_quote() {
+    assert_eq!(quote('a'), "\"a\"");
+    assert_eq!(quote('"'), "\"\\\"\"");
+    assert_eq!(quote('\\'), "\"\\\\\"");
+    assert_eq!(quote('\t'), "\"\\u0009\"");
+    assert_eq!(quote('\u{1f}'), "\"\\u001f\"");
+    // Rust's Debug would write "\u{301}", which is not JSON.
+    assert_eq!(quote('\u{301}'), "\"\u{301}\"");
+}
@@ -1,94 +1,96 @@
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::mem::size_of;
-use std::str::FromStr;
+use std::fs;
+use std::time::Instant;
+
+use day_6::stats::Survey;
+use day_6::{parse_groups, Group, Quorum};
 
 type Groups = Vec<Group>;
 
-#[derive(Debug)]
-struct Group {
-    number: u32,
-    questions: HashMap<char, u32>,
+fn get_groups() -> Groups {
+    let text = fs::read_to_string("input.txt").expect("Unable to open file");
+    parse_groups(&text)
 }
 
-impl Group {
-    fn new() -> Self {
-        Group {
-            number: 0,
-            questions: HashMap::new(),
-        }
-    }
+fn anyone(groups: &Groups) -> usize {
+    groups.iter().map(|group| group.anyone().len()).sum()
 }
 
-impl FromStr for Group {
-    type Err = &'static str;
-
-    fn from_str(s: &str) -> Result<Group, Self::Err> {
-        let mut group = Group::new();
-
-        s.split_whitespace().for_each(|line| {
-            group.number += 1;
-            line.chars().for_each(|c| {
-                let value = group.questions.entry(c).or_insert(0);
-                *value += 1;
-            });
-        });
-        Ok(group)
-    }
+fn everyone(groups: &Groups) -> usize {
+    groups.iter().map(|group| group.everyone().len()).sum()
 }
 
-fn get_groups() -> Groups {
-    let file = File::open("input.txt").expect("Unable to open file");
-    let mut reader = io::BufReader::new(file);
-
-    let mut groups: Groups = Vec::with_capacity(25 * size_of::<Group>());
-    let mut reader_buffer = String::with_capacity(30);
-    let mut group_buffer = String::with_capacity(30 * 5);
-
-    loop {
-        let no_of_bytes = reader.read_line(&mut reader_buffer).unwrap_or(0);
-
-        group_buffer.push_str(&reader_buffer);
-        if &reader_buffer == "\n" || &reader_buffer == "" {
-            groups.push(group_buffer.parse().unwrap());
-            group_buffer.clear();
-        }
+fn part_1(groups: &Groups) {
+    println!("Answer: {}", anyone(groups));
+}
 
-        reader_buffer.clear();
-        if no_of_bytes == 0 {
-            break;
-        }
-    }
-    groups
+fn part_2(groups: &Groups) {
+    println!("Answer: {}", everyone(groups));
 }
 
-fn part_1(groups: &Groups) {
-    let sum: usize = groups.iter().map(|group| group.questions.len()).sum();
-    println!("Answer: {}", sum);
+/// Times the bitsets against counting through `Group::questions`.
+fn benchmark(groups: &Groups) {
+    let rounds = 100;
+    let runs: [(&str, &dyn Fn() -> usize); 4] = [
+        ("Counts, anyone", &|| day_6::total(groups, Quorum::ANYONE)),
+        ("Bitsets, anyone", &|| anyone(groups)),
+        ("Counts, everyone", &|| {
+            day_6::total(groups, Quorum::EVERYONE)
+        }),
+        ("Bitsets, everyone", &|| everyone(groups)),
+    ];
+    for (name, run) in runs.iter() {
+        let start = Instant::now();
+        let answer = (0..rounds).fold(0, |_, _| run());
+        println!("{}: {}", name, answer);
+        println!("\tTime Taken: {:?}", start.elapsed() / rounds);
+    }
 }
 
-fn part_2(groups: &Groups) {
-    let sum: usize = groups
-        .iter()
-        .map(|group| {
-            group
-                .questions
-                .iter()
-                .filter(|entry| *entry.1 == group.number)
-                .count()
-        })
-        .sum();
-    println!("Answer: {}", sum);
+fn query(groups: &Groups, quorum: Quorum) {
+    groups.iter().enumerate().for_each(|(index, group)| {
+        let questions = group.questions_where(quorum);
+        let list: String = questions.iter().collect();
+        println!("Group {}: {} {}", index + 1, questions.len(), list);
+    });
+    println!("Total: {}", day_6::total(groups, quorum));
 }
 
 fn main() {
     let groups = get_groups();
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let arg = args.first().cloned();
+    if arg.as_deref() == Some("stats") {
+        // `stats [json|questions.csv|groups.csv]`, a summary by default.
+        let survey = Survey::new(&groups);
+        match args.get(1).map(String::as_str) {
+            None => print!("{}", survey),
+            Some("json") => print!("{}", survey.to_json()),
+            Some("questions.csv") => print!("{}", survey.questions_csv()),
+            Some("groups.csv") => print!("{}", survey.groups_csv()),
+            Some(other) => eprintln!("Unknown format: {}", other),
+        }
+        return;
+    }
+
     println!("Part 1: \n----------");
     part_1(&groups);
 
     println!("----------");
     println!("Part 2: \n----------");
     part_2(&groups);
+
+    if arg.as_deref() == Some("bench") {
+        println!("----------");
+        println!("Benchmark: \n----------");
+        benchmark(&groups);
+        return;
+    }
+
+    // Any other quorum, e.g. `at-least:2`, per group and in total.
+    if let Some(quorum) = arg {
+        println!("----------");
+        println!("Query {}: \n----------", quorum);
+        query(&groups, quorum.parse().expect("Invalid query"));
+    }
 }
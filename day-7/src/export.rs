@@ -0,0 +1,208 @@
+// Drawing the rules as a graph, for Graphviz or Mermaid.
+use std::fmt::Write;
+use std::str::FromStr;
+
+use crate::{BagGraph, BagId};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Graphviz, e.g. `dot -Tsvg`.
+    Dot,
+    Mermaid,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "dot" => Ok(Format::Dot),
+            "mermaid" => Ok(Format::Mermaid),
+            _ => Err(format!("unknown format {:?}, expected dot or mermaid", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// The bags that can eventually hold it.
+    Ancestors,
+    /// The bags it eventually holds.
+    Descendants,
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Direction, String> {
+        match s {
+            "ancestors" => Ok(Direction::Ancestors),
+            "descendants" => Ok(Direction::Descendants),
+            _ => Err(format!(
+                "unknown direction {:?}, expected ancestors or descendants",
+                s
+            )),
+        }
+    }
+}
+
+/// Writes bags as nodes and rules as edges from the outer bag to the inner
+/// one, labelled with how many it holds.
+pub struct Export<'a> {
+    graph: &'a BagGraph,
+    focus: Option<(BagId, Direction)>,
+}
+
+impl<'a> Export<'a> {
+    pub fn new(graph: &'a BagGraph) -> Self {
+        Export { graph, focus: None }
+    }
+
+    /// Only writes `bag` and its ancestors or descendants, with `bag`
+    /// highlighted.
+    pub fn around(mut self, bag: BagId, direction: Direction) -> Self {
+        self.focus = Some((bag, direction));
+        self
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Dot => self.to_dot(),
+            Format::Mermaid => self.to_mermaid(),
+        }
+    }
+
+    /// The bags to draw, in ID order.
+    fn selected(&self) -> Vec<bool> {
+        match self.focus {
+            None => vec![true; self.graph.len()],
+            Some((bag, direction)) => {
+                let mut selected = vec![false; self.graph.len()];
+                selected[bag] = true;
+                let others = match direction {
+                    Direction::Ancestors => self.graph.ancestors(bag),
+                    Direction::Descendants => self.graph.descendants(bag),
+                };
+                for other in others {
+                    selected[other] = true;
+                }
+                selected
+            }
+        }
+    }
+
+    /// Every rule between two selected bags, as (outer, inner, count).
+    fn edges(&self, selected: &[bool]) -> Vec<(BagId, BagId, usize)> {
+        self.graph
+            .bags()
+            .filter(|&bag| selected[bag])
+            .flat_map(|bag| {
+                self.graph
+                    .contents(bag)
+                    .iter()
+                    .filter(|edge| selected[edge.bag])
+                    .map(move |edge| (bag, edge.bag, edge.count))
+            })
+            .collect()
+    }
+
+    fn is_focus(&self, bag: BagId) -> bool {
+        self.focus.map(|x| x.0) == Some(bag)
+    }
+
+    pub fn to_dot(&self) -> String {
+        let selected = self.selected();
+        let mut dot = String::from("digraph bags {\n");
+        for bag in self.graph.bags().filter(|&bag| selected[bag]) {
+            let _ = write!(dot, "    b{} [label={}", bag, quote(self.graph.name(bag)));
+            if self.is_focus(bag) {
+                dot.push_str(", style=filled, fillcolor=gold, penwidth=2");
+            }
+            dot.push_str("];\n");
+        }
+        for (outer, inner, count) in self.edges(&selected) {
+            let _ = writeln!(dot, "    b{} -> b{} [label=\"{}\"];", outer, inner, count);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let selected = self.selected();
+        let mut mermaid = String::from("graph TD\n");
+        for bag in self.graph.bags().filter(|&bag| selected[bag]) {
+            let name = self.graph.name(bag).replace('"', "#quot;");
+            let _ = writeln!(mermaid, "    b{}[\"{}\"]", bag, name);
+        }
+        for (outer, inner, count) in self.edges(&selected) {
+            let _ = writeln!(mermaid, "    b{} -->|{}| b{}", outer, count, inner);
+        }
+        if let Some((bag, _)) = self.focus {
+            let _ = writeln!(mermaid, "    style b{} fill:gold,stroke-width:2px", bag);
+        }
+        mermaid
+    }
+}
+
+/// A DOT string holding `s`.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[test]
+fn test_dot() {
+    let graph: BagGraph = "a b bags contain 2 c d bags.\n\
+                           c d bags contain no other bags."
+        .parse()
+        .unwrap();
+    assert_eq!(
+        Export::new(&graph).to_dot(),
+        "digraph bags {\n    \
+             b0 [label=\"a b\"];\n    \
+             b1 [label=\"c d\"];\n    \
+             b0 -> b1 [label=\"2\"];\n\
+         }\n"
+    );
+    assert_eq!(quote("say \"hi\""), "\"say \\\"hi\\\"\"");
+}
+
+#[test]
+fn test_around() {
+    let graph: BagGraph = crate::EXAMPLE.parse().unwrap();
+    let gold = graph.id("shiny gold").unwrap();
+
+    let up = Export::new(&graph)
+        .around(gold, Direction::Ancestors)
+        .to_dot();
+    let nodes = up
+        .lines()
+        .filter(|x| x.contains("[label=") && !x.contains("->"));
+    assert_eq!(nodes.count(), 5);
+    assert_eq!(up.matches(" -> ").count(), 6);
+    assert!(up.contains(&format!(
+        "b{} [label=\"shiny gold\", style=filled, fillcolor=gold, penwidth=2];\n",
+        gold
+    )));
+    assert!(!up.contains("faded blue"));
+
+    let down = Export::new(&graph)
+        .around(gold, Direction::Descendants)
+        .render(Format::Mermaid);
+    assert!(down.starts_with("graph TD\n"));
+    assert_eq!(down.matches(" -->|").count(), 6);
+    assert!(down.contains(&format!(
+        "b{} -->|2| b{}",
+        gold,
+        graph.id("vibrant plum").unwrap()
+    )));
+    assert!(down.ends_with(&format!("    style b{} fill:gold,stroke-width:2px\n", gold)));
+    assert!(!down.contains("light red"));
+}
+
+#[test]
+fn test_parse_options() {
+    assert_eq!("mermaid".parse(), Ok(Format::Mermaid));
+    assert_eq!("ancestors".parse(), Ok(Direction::Ancestors));
+    assert!("svg".parse::<Format>().is_err());
+    assert!("up".parse::<Direction>().is_err());
+}
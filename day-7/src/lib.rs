@@ -0,0 +1,373 @@
+// --- Day 7: Handy Haversacks ---
+//
+// Pieces shared by the day 7 solutions.
+pub mod export;
+pub mod query;
+pub mod validate;
+
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::validate::Problem;
+
+/// A bag colour, interned by `BagGraph`.
+pub type BagId = usize;
+
+/// `count` bags of colour `bag`. In `BagGraph::contents` that is what a bag
+/// holds; in `BagGraph::containers` it is how many of the bag `bag` holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Edge {
+    pub bag: BagId,
+    pub count: usize,
+}
+
+/// One line of the rules, e.g. `light red bags contain 1 bright white bag,
+/// 2 muted yellow bags.`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub colour: String,
+    pub contents: Vec<(usize, String)>,
+}
+
+/// The colour of `bags`, e.g. `shiny gold` from `shiny gold bags`.
+fn colour(bags: &str) -> Result<&str, String> {
+    let bags = bags.trim();
+    bags.strip_suffix(" bags")
+        .or_else(|| bags.strip_suffix(" bag"))
+        .map(str::trim)
+        .filter(|colour| !colour.is_empty())
+        .ok_or_else(|| format!("expected COLOUR bags, found {:?}", bags))
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Rule, String> {
+        let s = s.trim();
+        let s = s.strip_suffix('.').unwrap_or(s);
+        let (outer, inner) = s
+            .split_once(" contain ")
+            .ok_or_else(|| format!("expected COLOUR bags contain ..., found {:?}", s))?;
+
+        let mut contents = Vec::new();
+        if inner.trim() != "no other bags" {
+            for part in inner.split(',') {
+                let (count, bags) = part
+                    .trim()
+                    .split_once(' ')
+                    .ok_or_else(|| format!("expected COUNT COLOUR bags, found {:?}", part))?;
+                let count = count
+                    .parse()
+                    .map_err(|_| format!("expected a count, found {:?}", count))?;
+                contents.push((count, colour(bags)?.to_string()));
+            }
+        }
+        Ok(Rule {
+            colour: colour(outer)?.to_string(),
+            contents,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum RulesError {
+    Io(io::Error),
+    Syntax { line: usize, message: String },
+    Invalid(Vec<Problem>),
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RulesError::Io(e) => write!(f, "unable to read rules: {}", e),
+            RulesError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            RulesError::Invalid(problems) => {
+                let problems: Vec<String> = problems.iter().map(|x| x.to_string()).collect();
+                f.write_str(&problems.join("\n"))
+            }
+        }
+    }
+}
+
+impl Error for RulesError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RulesError::Io(e) => Some(e),
+            RulesError::Syntax { .. } | RulesError::Invalid(_) => None,
+        }
+    }
+}
+
+/// Every bag colour and what it holds, with each colour stored once and
+/// referred to by a `BagId`. Edges are kept both ways round, so "what does
+/// X hold" and "what holds X" are both a lookup.
+#[derive(Debug, Clone, Default)]
+pub struct BagGraph {
+    names: Vec<String>,
+    ids: HashMap<String, BagId>,
+    contents: Vec<Vec<Edge>>,
+    containers: Vec<Vec<Edge>>,
+    /// The lines with a rule for each bag; only the first is used.
+    defined: Vec<Vec<usize>>,
+    /// The first line that puts each bag inside another.
+    used: Vec<Option<usize>>,
+}
+
+impl BagGraph {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Reads and validates the rules in `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<BagGraph, RulesError> {
+        fs::read_to_string(path).map_err(RulesError::Io)?.parse()
+    }
+
+    /// Reads rules without checking them, so `validate` can say what is
+    /// wrong with them. Only syntax errors stop it.
+    pub fn parse_unchecked(text: &str) -> Result<BagGraph, RulesError> {
+        let mut graph = BagGraph::new();
+        for (index, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let rule: Rule = line.parse().map_err(|message| RulesError::Syntax {
+                line: index + 1,
+                message,
+            })?;
+            graph.add_rule(&rule, index + 1);
+        }
+        Ok(graph)
+    }
+
+    /// The ID for `colour`, adding it if it is new.
+    pub fn intern(&mut self, colour: &str) -> BagId {
+        if let Some(&id) = self.ids.get(colour) {
+            return id;
+        }
+        let id = self.names.len();
+        self.names.push(colour.to_string());
+        self.ids.insert(colour.to_string(), id);
+        self.contents.push(Vec::new());
+        self.containers.push(Vec::new());
+        self.defined.push(Vec::new());
+        self.used.push(None);
+        id
+    }
+
+    /// Adds the rule found on `line`. A colour's first rule is the one
+    /// that counts; later ones are only noted for `validate`.
+    pub fn add_rule(&mut self, rule: &Rule, line: usize) -> BagId {
+        let outer = self.intern(&rule.colour);
+        self.defined[outer].push(line);
+        if self.defined[outer].len() > 1 {
+            return outer;
+        }
+        for (count, colour) in &rule.contents {
+            let inner = self.intern(colour);
+            self.used[inner].get_or_insert(line);
+            self.add_edge(outer, inner, *count);
+        }
+        outer
+    }
+
+    pub fn add_edge(&mut self, outer: BagId, inner: BagId, count: usize) {
+        self.contents[outer].push(Edge { bag: inner, count });
+        self.containers[inner].push(Edge { bag: outer, count });
+    }
+
+    pub fn id(&self, colour: &str) -> Option<BagId> {
+        self.ids.get(colour).copied()
+    }
+
+    pub fn name(&self, bag: BagId) -> &str {
+        &self.names[bag]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn bags(&self) -> impl Iterator<Item = BagId> {
+        0..self.len()
+    }
+
+    /// What `bag` holds directly.
+    pub fn contents(&self, bag: BagId) -> &[Edge] {
+        &self.contents[bag]
+    }
+
+    /// The bags that hold `bag` directly.
+    pub fn containers(&self, bag: BagId) -> &[Edge] {
+        &self.containers[bag]
+    }
+
+    /// Every bag that can eventually hold `bag`, nearest first.
+    pub fn ancestors(&self, bag: BagId) -> Vec<BagId> {
+        self.reachable(bag, &self.containers)
+    }
+
+    /// Every bag `bag` eventually holds, nearest first.
+    pub fn descendants(&self, bag: BagId) -> Vec<BagId> {
+        self.reachable(bag, &self.contents)
+    }
+
+    /// A breadth-first walk from `start`, visiting each bag once so shared
+    /// sub-trees cost nothing extra. `start` itself is left out even if
+    /// the rules lead back to it.
+    fn reachable(&self, start: BagId, edges: &[Vec<Edge>]) -> Vec<BagId> {
+        let mut seen = vec![false; self.len()];
+        let mut queue = VecDeque::new();
+        let mut found = Vec::new();
+        seen[start] = true;
+        queue.push_back(start);
+        while let Some(bag) = queue.pop_front() {
+            for edge in &edges[bag] {
+                if !seen[edge.bag] {
+                    seen[edge.bag] = true;
+                    found.push(edge.bag);
+                    queue.push_back(edge.bag);
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Validated rules; see `BagGraph::validate`.
+impl FromStr for BagGraph {
+    type Err = RulesError;
+
+    fn from_str(s: &str) -> Result<BagGraph, RulesError> {
+        let graph = BagGraph::parse_unchecked(s)?;
+        graph.validate().map_err(RulesError::Invalid)?;
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = include_str!("../test.txt");
+
+/// `layers` rows of `width` bags, each holding two of every bag in the row
+/// below: far too many paths to walk one at a time.
+#[cfg(test)]
+fn layered(layers: usize, width: usize) -> BagGraph {
+    let mut graph = BagGraph::new();
+    for layer in 0..layers {
+        for i in 0..width {
+            graph.intern(&format!("layer{} bag{}", layer, i));
+        }
+    }
+    for outer in 0..(layers - 1) * width {
+        let below = (outer / width + 1) * width;
+        for inner in below..below + width {
+            graph.add_edge(outer, inner, 2);
+        }
+    }
+    graph
+}
+
+#[test]
+fn test_rule() {
+    let rule: Rule = "light red bags contain 1 bright white bag, 2 muted yellow bags."
+        .parse()
+        .unwrap();
+    assert_eq!(rule.colour, "light red");
+    assert_eq!(
+        rule.contents,
+        vec![
+            (1, "bright white".to_string()),
+            (2, "muted yellow".to_string())
+        ]
+    );
+
+    let empty: Rule = "faded blue bags contain no other bags.".parse().unwrap();
+    assert!(empty.contents.is_empty());
+
+    assert!("faded blue bags".parse::<Rule>().is_err());
+    assert!("faded blue bags contain some bags."
+        .parse::<Rule>()
+        .is_err());
+    assert!("faded blue bags contain 2 red.".parse::<Rule>().is_err());
+    assert!("bags contain 2 red bags.".parse::<Rule>().is_err());
+}
+
+#[test]
+fn test_graph() {
+    let graph: BagGraph = EXAMPLE.parse().unwrap();
+    assert_eq!(graph.len(), 9);
+
+    let gold = graph.id("shiny gold").unwrap();
+    let names = |edges: &[Edge]| -> Vec<(usize, &str)> {
+        edges.iter().map(|x| (x.count, graph.name(x.bag))).collect()
+    };
+    assert_eq!(
+        names(graph.contents(gold)),
+        vec![(1, "dark olive"), (2, "vibrant plum")]
+    );
+    assert_eq!(
+        names(graph.containers(gold)),
+        vec![(1, "bright white"), (2, "muted yellow")]
+    );
+    assert!(graph.contents(graph.id("faded blue").unwrap()).is_empty());
+    assert_eq!(graph.id("plaid magenta"), None);
+
+    match "light red bags contain 1 bright white bag.\nnonsense".parse::<BagGraph>() {
+        Err(RulesError::Syntax { line, .. }) => assert_eq!(line, 2),
+        other => panic!("expected a syntax error, got {:?}", other),
+    }
+    match "light red bags contain 1 bright white bag.".parse::<BagGraph>() {
+        Err(e @ RulesError::Invalid(_)) => {
+            assert_eq!(e.to_string(), "line 1: no rule for \"bright white\" bags")
+        }
+        other => panic!("expected invalid rules, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_ancestors() {
+    let graph: BagGraph = EXAMPLE.parse().unwrap();
+    let names = |bags: Vec<BagId>| -> Vec<&str> {
+        let mut names: Vec<&str> = bags.into_iter().map(|x| graph.name(x)).collect();
+        names.sort_unstable();
+        names
+    };
+    let gold = graph.id("shiny gold").unwrap();
+    assert_eq!(
+        names(graph.ancestors(gold)),
+        vec!["bright white", "dark orange", "light red", "muted yellow"]
+    );
+    assert_eq!(
+        names(graph.ancestors(graph.id("dark olive").unwrap())).len(),
+        5
+    );
+    assert!(graph.ancestors(graph.id("light red").unwrap()).is_empty());
+    assert_eq!(
+        names(graph.descendants(gold)),
+        vec!["dark olive", "dotted black", "faded blue", "vibrant plum"]
+    );
+
+    let mut cyclic = BagGraph::new();
+    let (a, b) = (cyclic.intern("a"), cyclic.intern("b"));
+    cyclic.add_edge(a, b, 1);
+    cyclic.add_edge(b, a, 1);
+    assert_eq!(cyclic.ancestors(a), vec![b]);
+}
+
+#[test]
+fn test_ancestors_scale() {
+    let graph = layered(10_000, 10);
+    assert_eq!(graph.len(), 100_000);
+    assert_eq!(graph.ancestors(graph.len() - 1).len(), 99_990);
+    assert_eq!(graph.descendants(0).len(), 99_990);
+    assert_eq!(graph.ancestors(55).len(), 50);
+}
@@ -1,78 +1,68 @@
 // --- Day 7: Handy Haversacks ---
 //
 // https://adventofcode.com/2020/day/7
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::env;
+use std::process;
 
-type Color = String;
-type Rule = (usize, Color);
-type Rules = Vec<Rule>;
-type RuleSet = HashMap<Color, Rules>;
+use day_7::export::{Direction, Export, Format};
+use day_7::query::Query;
+use day_7::{BagGraph, RulesError};
 
-fn part_one(rs: &RuleSet, c: &str) -> usize {
-    let mut count = 0;
-    for color in rs.keys() {
-        count += recursive_find(rs, c, rs.get(color).unwrap());
-    }
-    count
+fn part_one(graph: &BagGraph, c: &str) -> usize {
+    graph.ancestors(graph.id(c).unwrap()).len()
 }
 
-fn recursive_find(rs: &RuleSet, c: &str, rules: &[Rule]) -> usize {
-    for rule in rules {
-        if rule.1 == c || recursive_find(rs, c, rs.get(&rule.1).unwrap()) == 1 {
-            return 1;
-        }
-    }
-    0
+fn part_two(graph: &BagGraph, c: &str) -> usize {
+    graph.total_inside(graph.id(c).unwrap())
 }
 
-fn part_two(rs: &RuleSet, c: &str) -> usize {
-    let rules = rs.get(c).unwrap();
-    if rules.is_empty() {
-        0
-    } else {
-        let mut count = 0;
-        for rule in rules {
-            count += rule.0 + rule.0 * part_two(rs, &rule.1)
+fn main() {
+    let ruleset = match load_input("input.txt") {
+        Ok(ruleset) => ruleset,
+        Err(e) => {
+            eprintln!("input.txt: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // `matt export dot|mermaid [ancestors|descendants COLOUR]` draws the
+    // rules on stdout, optionally just those around one colour.
+    if args.first().map(String::as_str) == Some("export") {
+        let format: Format = args.get(1).map_or("dot", String::as_str).parse().unwrap();
+        let mut export = Export::new(&ruleset);
+        if let Some(direction) = args.get(2) {
+            let direction: Direction = direction.parse().unwrap();
+            let colour = args[3..].join(" ");
+            let bag = ruleset
+                .id(&colour)
+                .unwrap_or_else(|| panic!("no rule for {:?} bags", colour));
+            export = export.around(bag, direction);
         }
-        count
+        print!("{}", export.render(format));
+        return;
     }
-}
 
-fn main() {
-    let ruleset = load_input("input.txt").unwrap();
+    // `matt query containers|total|tree COLOUR` or
+    // `matt query chain OUTER to INNER` asks about any colour.
+    if args.first().map(String::as_str) == Some("query") {
+        match Query::from_args(&args[1..]).and_then(|x| x.answer(&ruleset)) {
+            Ok(answer) => print!("{}", answer),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
 
     println!("Part One: {} ", part_one(&ruleset, "shiny gold"));
     println!("Part Two: {} ", part_two(&ruleset, "shiny gold"));
 }
 
-fn parse_rule_line(l: String) -> (Color, Rules) {
-    let mut rules: Rules = vec![];
-    let parts: Vec<_> = l.split("bags contain").collect();
-    let color: Color = parts[0].trim().to_string();
-    for part in parts[1].split(',') {
-        if part.contains("no other bags") {
-            continue;
-        }
-        let mut subs = part.trim().split(' ');
-        let num: usize = subs.next().unwrap().trim().parse().unwrap();
-        let sub_color: String = subs.take(2).fold(String::new(), |acc, x| {
-            format!("{} {}", acc, x).trim().to_string()
-        });
-        rules.push((num, sub_color));
-    }
-    (color, rules)
-}
-fn load_input(fname: &str) -> io::Result<RuleSet> {
-    let file = File::open(fname)?;
-    let buf = io::BufReader::new(file);
-    let mut ruleset: RuleSet = HashMap::new();
-    for line in buf.lines() {
-        let (color, rules) = parse_rule_line(line?);
-        ruleset.insert(color, rules);
-    }
-    Ok(ruleset)
+fn load_input(fname: &str) -> Result<BagGraph, RulesError> {
+    BagGraph::load(fname)
 }
 
 #[test]
@@ -0,0 +1,345 @@
+// Questions about any colour, not just shiny gold.
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+use crate::{BagGraph, BagId};
+
+/// These assume rules without cycles, as `BagGraph::validate` checks.
+/// Totals saturate at `usize::MAX` rather than overflow.
+impl BagGraph {
+    /// `start` and every bag it eventually holds, each after everything it
+    /// holds.
+    fn postorder(&self, start: BagId) -> Vec<BagId> {
+        let mut seen = vec![false; self.len()];
+        let mut order = Vec::new();
+        let mut path = vec![(start, 0)];
+        seen[start] = true;
+        while let Some(&mut (bag, ref mut next)) = path.last_mut() {
+            match self.contents(bag).get(*next) {
+                Some(edge) => {
+                    *next += 1;
+                    if !seen[edge.bag] {
+                        seen[edge.bag] = true;
+                        path.push((edge.bag, 0));
+                    }
+                }
+                None => {
+                    order.push(bag);
+                    path.pop();
+                }
+            }
+        }
+        order
+    }
+
+    /// How many bags `bag` holds, counting bags inside bags.
+    pub fn total_inside(&self, bag: BagId) -> usize {
+        let mut totals = vec![0; self.len()];
+        for outer in self.postorder(bag) {
+            totals[outer] = self.contents(outer).iter().fold(0usize, |sum, edge| {
+                let each = totals[edge.bag].saturating_add(1);
+                sum.saturating_add(edge.count.saturating_mul(each))
+            });
+        }
+        totals[bag]
+    }
+
+    /// The fewest bags from `outer` down to `inner`, both included.
+    pub fn shortest_chain(&self, outer: BagId, inner: BagId) -> Option<Vec<BagId>> {
+        let mut parents = vec![None; self.len()];
+        let mut queue = VecDeque::new();
+        parents[outer] = Some(outer);
+        queue.push_back(outer);
+        while let Some(bag) = queue.pop_front() {
+            if bag == inner {
+                return Some(self.chain(&parents, outer, inner));
+            }
+            for edge in self.contents(bag) {
+                if parents[edge.bag].is_none() {
+                    parents[edge.bag] = Some(bag);
+                    queue.push_back(edge.bag);
+                }
+            }
+        }
+        None
+    }
+
+    /// The most bags from `outer` down to `inner`, both included.
+    pub fn longest_chain(&self, outer: BagId, inner: BagId) -> Option<Vec<BagId>> {
+        let mut parents = vec![None; self.len()];
+        let mut lengths = vec![0; self.len()];
+        parents[outer] = Some(outer);
+        // Reversed, every bag comes before anything it holds.
+        for bag in self.postorder(outer).into_iter().rev() {
+            for edge in self.contents(bag) {
+                if parents[edge.bag].is_none() || lengths[bag] + 1 > lengths[edge.bag] {
+                    lengths[edge.bag] = lengths[bag] + 1;
+                    parents[edge.bag] = Some(bag);
+                }
+            }
+        }
+        parents[inner].map(|_| self.chain(&parents, outer, inner))
+    }
+
+    /// Follows `parents` back from `inner` to `outer`.
+    fn chain(&self, parents: &[Option<BagId>], outer: BagId, inner: BagId) -> Vec<BagId> {
+        let mut chain = vec![inner];
+        let mut bag = inner;
+        while bag != outer {
+            bag = parents[bag].unwrap();
+            chain.push(bag);
+        }
+        chain.reverse();
+        chain
+    }
+
+    /// Everything inside `bag`, one line per bag, e.g.
+    ///
+    /// ```text
+    /// shiny gold
+    /// |-- 1 dark olive
+    /// |   `-- 3 faded blue
+    /// `-- 2 vibrant plum
+    ///     `-- 5 faded blue (10 in all)
+    /// ```
+    ///
+    /// A colour held in several places is written out in each of them.
+    pub fn contents_tree(&self, bag: BagId) -> String {
+        let mut tree = format!("{}\n", self.name(bag));
+        // (bag, how many, how many in all, indent, last in its bag)
+        let mut stack: Vec<(BagId, usize, usize, String, bool)> = Vec::new();
+        let push_contents = |stack: &mut Vec<_>, outer: BagId, outer_all: usize, indent: &str| {
+            let contents = self.contents(outer);
+            for (index, edge) in contents.iter().enumerate().rev() {
+                stack.push((
+                    edge.bag,
+                    edge.count,
+                    outer_all.saturating_mul(edge.count),
+                    indent.to_string(),
+                    index + 1 == contents.len(),
+                ));
+            }
+        };
+        push_contents(&mut stack, bag, 1, "");
+        while let Some((inner, count, all, indent, last)) = stack.pop() {
+            let branch = if last { "`-- " } else { "|-- " };
+            let _ = write!(tree, "{}{}{} {}", indent, branch, count, self.name(inner));
+            if all != count {
+                let _ = write!(tree, " ({} in all)", all);
+            }
+            tree.push('\n');
+            let indent = indent + if last { "    " } else { "|   " };
+            push_contents(&mut stack, inner, all, &indent);
+        }
+        tree
+    }
+}
+
+/// A question about the rules, as given on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Query {
+    /// `containers COLOUR`: which bags can eventually hold it.
+    Containers(String),
+    /// `total COLOUR`: how many bags it holds in all.
+    Total(String),
+    /// `chain OUTER to INNER`: the shortest and longest ways one ends up
+    /// inside the other.
+    Chain { outer: String, inner: String },
+    /// `tree COLOUR`: everything it holds, laid out.
+    Tree(String),
+}
+
+impl Query {
+    /// Colours may be one argument each or split into words, so both
+    /// `chain "light red" "shiny gold"` and `chain light red to shiny gold`
+    /// work.
+    pub fn from_args<S: AsRef<str>>(args: &[S]) -> Result<Query, String> {
+        let words: Vec<&str> = args.iter().map(|x| x.as_ref()).collect();
+        let colour = |words: &[&str]| -> Result<String, String> {
+            match words.join(" ").trim() {
+                "" => Err("expected a colour".to_string()),
+                colour => Ok(colour.to_string()),
+            }
+        };
+        match words.split_first() {
+            Some((&"containers", rest)) => colour(rest).map(Query::Containers),
+            Some((&"total", rest)) => colour(rest).map(Query::Total),
+            Some((&"tree", rest)) => colour(rest).map(Query::Tree),
+            Some((&"chain", rest)) => {
+                let (outer, inner) = match rest.iter().position(|&x| x == "to") {
+                    Some(to) => (&rest[..to], &rest[to + 1..]),
+                    None if rest.len() == 2 => rest.split_at(1),
+                    None => return Err("expected chain OUTER to INNER".to_string()),
+                };
+                Ok(Query::Chain {
+                    outer: colour(outer)?,
+                    inner: colour(inner)?,
+                })
+            }
+            _ => Err(format!(
+                "unknown query {:?}, expected containers, total, chain or tree",
+                words.join(" ")
+            )),
+        }
+    }
+
+    /// The answer, ready to print.
+    pub fn answer(&self, graph: &BagGraph) -> Result<String, String> {
+        let id = |colour: &str| {
+            graph
+                .id(colour)
+                .ok_or_else(|| format!("no rule for {:?} bags", colour))
+        };
+        let names = |bags: &[BagId]| -> Vec<&str> { bags.iter().map(|&x| graph.name(x)).collect() };
+
+        Ok(match self {
+            Query::Containers(colour) => {
+                let mut containers = names(&graph.ancestors(id(colour)?));
+                containers.sort_unstable();
+                let mut text = format!(
+                    "{} bags can eventually hold {} bags\n",
+                    containers.len(),
+                    colour
+                );
+                for name in containers {
+                    let _ = writeln!(text, "{}", name);
+                }
+                text
+            }
+            Query::Total(colour) => format!(
+                "{} bags hold {} other bags\n",
+                colour,
+                graph.total_inside(id(colour)?)
+            ),
+            Query::Chain { outer, inner } => {
+                let (from, to) = (id(outer)?, id(inner)?);
+                let shortest = graph.shortest_chain(from, to);
+                let longest = graph.longest_chain(from, to);
+                match (shortest, longest) {
+                    (Some(shortest), Some(longest)) => {
+                        let line = |label: &str, chain: &[BagId]| {
+                            format!(
+                                "{}: {} ({} deep)\n",
+                                label,
+                                names(chain).join(" -> "),
+                                chain.len() - 1
+                            )
+                        };
+                        line("Shortest", &shortest) + &line("Longest", &longest)
+                    }
+                    _ => format!("{} bags never end up in {} bags\n", inner, outer),
+                }
+            }
+            Query::Tree(colour) => graph.contents_tree(id(colour)?),
+        })
+    }
+}
+
+#[test]
+fn test_totals() {
+    let graph = BagGraph::load("test2.txt").unwrap();
+    assert_eq!(graph.total_inside(graph.id("shiny gold").unwrap()), 126);
+    assert_eq!(graph.total_inside(graph.id("dark blue").unwrap()), 2);
+    assert_eq!(graph.total_inside(graph.id("dark violet").unwrap()), 0);
+
+    let example: BagGraph = crate::EXAMPLE.parse().unwrap();
+    assert_eq!(example.total_inside(example.id("shiny gold").unwrap()), 32);
+    assert_eq!(crate::layered(1_000, 10).total_inside(0), usize::MAX);
+}
+
+#[test]
+fn test_chains() {
+    let graph: BagGraph = crate::EXAMPLE.parse().unwrap();
+    let id = |colour| graph.id(colour).unwrap();
+    let names = |chain: Option<Vec<BagId>>| -> Option<Vec<&str>> {
+        chain.map(|x| x.into_iter().map(|x| graph.name(x)).collect())
+    };
+    assert_eq!(
+        names(graph.shortest_chain(id("light red"), id("faded blue"))),
+        Some(vec!["light red", "muted yellow", "faded blue"])
+    );
+    // Four ways down, all through shiny gold.
+    let longest = names(graph.longest_chain(id("light red"), id("faded blue"))).unwrap();
+    assert_eq!(longest.len(), 5);
+    assert_eq!(longest[2], "shiny gold");
+    assert_eq!(
+        names(graph.longest_chain(id("dark olive"), id("dark olive"))),
+        Some(vec!["dark olive"])
+    );
+    assert_eq!(
+        graph.shortest_chain(id("faded blue"), id("light red")),
+        None
+    );
+    assert_eq!(graph.longest_chain(id("faded blue"), id("light red")), None);
+
+    let layered = crate::layered(10_000, 10);
+    let last = layered.len() - 1;
+    assert_eq!(
+        layered.shortest_chain(3, last).map(|x| x.len()),
+        Some(10_000)
+    );
+    assert_eq!(
+        layered.longest_chain(3, last).map(|x| x.len()),
+        Some(10_000)
+    );
+}
+
+#[test]
+fn test_tree() {
+    let graph = BagGraph::load("test2.txt").unwrap();
+    assert_eq!(
+        graph.contents_tree(graph.id("dark orange").unwrap()),
+        "dark orange\n\
+         `-- 2 dark yellow\n    \
+             `-- 2 dark green (4 in all)\n        \
+                 `-- 2 dark blue (8 in all)\n            \
+                     `-- 2 dark violet (16 in all)\n"
+    );
+    let example: BagGraph = crate::EXAMPLE.parse().unwrap();
+    let tree = example.contents_tree(example.id("shiny gold").unwrap());
+    assert!(tree.starts_with("shiny gold\n|-- 1 dark olive\n|   |-- 3 faded blue\n"));
+    assert!(tree.ends_with("`-- 2 vibrant plum\n    |-- 5 faded blue (10 in all)\n    `-- 6 dotted black (12 in all)\n"));
+}
+
+#[test]
+fn test_queries() {
+    let graph: BagGraph = crate::EXAMPLE.parse().unwrap();
+    let ask = |args: &str| {
+        let args: Vec<&str> = args.split(' ').collect();
+        Query::from_args(&args).and_then(|x| x.answer(&graph))
+    };
+    assert_eq!(
+        ask("containers shiny gold").unwrap(),
+        "4 bags can eventually hold shiny gold bags\n\
+         bright white\ndark orange\nlight red\nmuted yellow\n"
+    );
+    assert_eq!(
+        ask("total shiny gold").unwrap(),
+        "shiny gold bags hold 32 other bags\n"
+    );
+    assert_eq!(
+        ask("chain muted yellow to faded blue").unwrap(),
+        "Shortest: muted yellow -> faded blue (1 deep)\n\
+         Longest: muted yellow -> shiny gold -> vibrant plum -> faded blue (3 deep)\n"
+    );
+    assert_eq!(
+        ask("chain faded blue to light red").unwrap(),
+        "light red bags never end up in faded blue bags\n"
+    );
+    assert!(ask("tree shiny gold").unwrap().starts_with("shiny gold\n"));
+    assert_eq!(
+        ask("total plaid magenta"),
+        Err("no rule for \"plaid magenta\" bags".to_string())
+    );
+
+    assert_eq!(
+        Query::from_args(&["chain", "light red", "shiny gold"]),
+        Ok(Query::Chain {
+            outer: "light red".to_string(),
+            inner: "shiny gold".to_string()
+        })
+    );
+    assert!(Query::from_args(&["chain", "a", "b", "c"]).is_err());
+    assert!(Query::from_args(&["total"]).is_err());
+    assert!(Query::from_args(&["biggest"]).is_err());
+}
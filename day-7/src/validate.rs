@@ -0,0 +1,195 @@
+// Checks that the rules describe bags that can actually be packed.
+use std::fmt;
+
+use crate::{BagGraph, BagId};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// A bag that ends up inside itself, e.g. `["a", "b", "a"]`.
+    Cycle { path: Vec<String> },
+    /// A colour that some rule holds but that has no rule of its own.
+    Missing { colour: String, line: usize },
+    /// A second rule for the same colour; the first one is kept.
+    Duplicate {
+        colour: String,
+        line: usize,
+        first: usize,
+    },
+}
+
+impl Problem {
+    /// The line the problem is reported on, if it belongs to one.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Problem::Cycle { .. } => None,
+            Problem::Missing { line, .. } | Problem::Duplicate { line, .. } => Some(*line),
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Cycle { path } => write!(f, "bags contain themselves: {}", path.join(" -> ")),
+            Problem::Missing { colour, line } => {
+                write!(f, "line {}: no rule for {:?} bags", line, colour)
+            }
+            Problem::Duplicate {
+                colour,
+                line,
+                first,
+            } => write!(
+                f,
+                "line {}: {:?} bags already have a rule on line {}",
+                line, colour, first
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    New,
+    Open,
+    Done,
+}
+
+impl BagGraph {
+    /// Every problem with the rules, by line, then the first cycle found.
+    /// Queries assume none of these, so check before asking anything.
+    pub fn validate(&self) -> Result<(), Vec<Problem>> {
+        let mut problems = Vec::new();
+        for bag in self.bags() {
+            let colour = || self.name(bag).to_string();
+            match (self.defined[bag].split_first(), self.used[bag]) {
+                (None, Some(line)) => problems.push(Problem::Missing {
+                    colour: colour(),
+                    line,
+                }),
+                (Some((&first, others)), _) => {
+                    problems.extend(others.iter().map(|&line| Problem::Duplicate {
+                        colour: colour(),
+                        line,
+                        first,
+                    }))
+                }
+                (None, None) => (),
+            }
+        }
+        problems.sort_by_key(|x| x.line());
+
+        if let Some(cycle) = self.find_cycle() {
+            problems.push(Problem::Cycle {
+                path: cycle.iter().map(|&x| self.name(x).to_string()).collect(),
+            });
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// A path that starts and ends on the same bag, if there is one. The
+    /// search keeps its own stack, so long chains of bags are fine.
+    pub fn find_cycle(&self) -> Option<Vec<BagId>> {
+        let mut visits = vec![Visit::New; self.len()];
+        for root in self.bags() {
+            if visits[root] != Visit::New {
+                continue;
+            }
+            // Each entry is a bag on the current path and its next edge.
+            let mut path = vec![(root, 0)];
+            visits[root] = Visit::Open;
+            while let Some(&mut (bag, ref mut next)) = path.last_mut() {
+                match self.contents(bag).get(*next) {
+                    Some(edge) => {
+                        *next += 1;
+                        match visits[edge.bag] {
+                            Visit::New => {
+                                visits[edge.bag] = Visit::Open;
+                                path.push((edge.bag, 0));
+                            }
+                            Visit::Open => {
+                                let start = path.iter().position(|x| x.0 == edge.bag).unwrap();
+                                let mut cycle: Vec<BagId> =
+                                    path[start..].iter().map(|x| x.0).collect();
+                                cycle.push(edge.bag);
+                                return Some(cycle);
+                            }
+                            Visit::Done => (),
+                        }
+                    }
+                    None => {
+                        visits[bag] = Visit::Done;
+                        path.pop();
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[test]
+fn test_valid() {
+    let graph: BagGraph = crate::EXAMPLE.parse().unwrap();
+    assert_eq!(graph.validate(), Ok(()));
+    assert_eq!(graph.find_cycle(), None);
+    assert_eq!(crate::layered(10_000, 10).find_cycle(), None);
+}
+
+#[test]
+fn test_problems() {
+    let graph = BagGraph::parse_unchecked(
+        "light red bags contain 1 dark blue bag, 2 muted yellow bags.\n\
+         dark blue bags contain 3 faded green bags.\n\
+         muted yellow bags contain no other bags.\n\
+         dark blue bags contain 1 muted yellow bag.\n",
+    )
+    .unwrap();
+    assert_eq!(
+        graph.validate(),
+        Err(vec![
+            Problem::Missing {
+                colour: "faded green".to_string(),
+                line: 2
+            },
+            Problem::Duplicate {
+                colour: "dark blue".to_string(),
+                line: 4,
+                first: 2
+            },
+        ])
+    );
+    // The first rule wins.
+    let blue = graph.id("dark blue").unwrap();
+    assert_eq!(graph.contents(blue).len(), 1);
+    assert_eq!(graph.name(graph.contents(blue)[0].bag), "faded green");
+}
+
+#[test]
+fn test_cycles() {
+    let graph = BagGraph::parse_unchecked(
+        "light red bags contain 1 dark blue bag.\n\
+         dark blue bags contain 3 faded green bags, 1 muted yellow bag.\n\
+         muted yellow bags contain no other bags.\n\
+         faded green bags contain 2 light red bags.\n",
+    )
+    .unwrap();
+    let problems = graph.validate().unwrap_err();
+    assert_eq!(problems.len(), 1);
+    assert_eq!(
+        problems[0].to_string(),
+        "bags contain themselves: light red -> dark blue -> faded green -> light red"
+    );
+
+    let itself = BagGraph::parse_unchecked("a b bags contain 2 a b bags.").unwrap();
+    assert_eq!(itself.find_cycle(), Some(vec![0, 0]));
+
+    // Long enough to overflow the stack if the search recursed.
+    let mut chain = crate::layered(200_000, 1);
+    chain.add_edge(199_999, 100_000, 1);
+    assert_eq!(chain.find_cycle().map(|x| x.len()), Some(100_001));
+}
@@ -1,125 +1,70 @@
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::ops;
+use std::env;
 use std::time::Instant;
 
-use itertools::Itertools;
+use day_7::query::Query;
+use day_7::{BagGraph, BagId};
 
-type Bags = HashMap<String, BagContents>;
-
-type BoolCache<'a> = HashMap<&'a str, bool>;
-type NumCache<'a> = HashMap<&'a str, u32>;
-
-#[derive(Debug)]
-struct BagContents {
-    data: String,
-    length: usize,
-}
-
-impl BagContents {
-    fn new(line: String) -> (String, Self) {
-        let (name, bags_str) = line.split("contain").collect_tuple().unwrap();
-
-        let length = match bags_str.contains("no other bag") {
-            true => 0,
-            false => bags_str.split(",").count(),
-        };
-        let name = name.split(" bag").nth(0).unwrap().to_string();
-        let contents = BagContents {
-            data: bags_str.to_string(),
-            length,
-        };
-
-        (name, contents)
-    }
-}
-
-impl ops::Index<usize> for BagContents {
-    type Output = str;
-
-    fn index<'a>(&'a self, index: usize) -> &'a str {
-        if index >= self.length {
-            panic!("Index {} is out of bounds", index);
+fn get_bags() -> BagGraph {
+    match BagGraph::load("../input.txt") {
+        Ok(bags) => bags,
+        Err(e) => {
+            eprintln!("Rejected input.txt, {}", e);
+            std::process::exit(1);
         }
-
-        let bag_str = self.data.split(",").nth(index).unwrap().trim();
-        bag_str.split(" bag").nth(0).unwrap().trim()
     }
 }
 
-fn get_bags() -> Bags {
-    let file = File::open("../input.txt").expect("Unable to open file");
-    io::BufReader::new(file)
-        .lines()
-        .filter_map(Result::ok)
-        .map(|line| BagContents::new(line))
-        .collect()
-}
-
-fn can_contain<'a, 'b>(cache: &'b mut BoolCache<'a>, bags: &'a Bags, bag: &'a str) -> bool {
-    if cache.contains_key(bag) {
-        return cache[bag];
-    }
-
-    let contents = &bags[bag];
-    let result = (0..contents.length).any(|index| {
-        let (_, inner_bag) = &contents[index].split_at(2);
-        *inner_bag == "shiny gold" || can_contain(cache, bags, inner_bag)
-    });
+fn part_1(bags: &BagGraph, bag: BagId) {
+    let count = bags.ancestors(bag).len();
 
-    cache.insert(bag, result);
-    result
+    println!("{} bags can contain {} bag", count, bags.name(bag));
 }
 
-fn part_1(bags: &Bags) {
-    let mut cache: BoolCache = HashMap::new();
-    let count = bags
-        .iter()
-        .filter(|entry| can_contain(&mut cache, bags, entry.0))
-        .count();
+fn part_2(bags: &BagGraph, bag: BagId) {
+    let count = bags.total_inside(bag);
 
-    println!("{} bags can contain shiny gold bag", count);
+    println!("{} bag can contain {} bags", bags.name(bag), count);
 }
 
-fn bag_count<'a, 'b>(cache: &'b mut NumCache<'a>, bags: &'a Bags, bag: &'a str) -> u32 {
-    if cache.contains_key(bag) {
-        return cache[bag];
-    }
-
-    let contents = &bags[bag];
-    let result = (0..contents.length)
-        .map(|index| {
-            let (number, inner_bag) = &contents[index].split_at(2);
-            let number: u32 = number.trim().parse().unwrap();
-
-            number + (number * bag_count(cache, bags, inner_bag))
-        })
-        .sum();
-
-    cache.insert(bag, result);
-    result
-}
-
-fn part_2(bags: &Bags) {
-    let mut cache: NumCache = HashMap::new();
-    let count = bag_count(&mut cache, bags, "shiny gold");
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let bags = get_bags();
 
-    println!("shiny gold bag can contain {} bags", count);
-}
+    if args.first().map(String::as_str) == Some("query") {
+        let start = Instant::now();
+        match Query::from_args(&args[1..]).and_then(|query| query.answer(&bags)) {
+            Ok(answer) => print!("{}", answer),
+            Err(e) => {
+                eprintln!("Rejected query, {}", e);
+                std::process::exit(1);
+            }
+        }
+        println!("Time Taken: {:?}", start.elapsed());
+        return;
+    }
 
-fn main() {
+    // The colour may be given as one argument or as several words.
+    let colour = match args.join(" ").trim() {
+        "" => "shiny gold".to_string(),
+        colour => colour.to_string(),
+    };
+    let bag = match bags.id(&colour) {
+        Some(bag) => bag,
+        None => {
+            eprintln!("Unknown bag colour {:?}", colour);
+            std::process::exit(1);
+        }
+    };
     println!("Part 1: \n----------");
-    let bags = get_bags();
 
     let mut start = Instant::now();
-    part_1(&bags);
+    part_1(&bags, bag);
     println!("Time Taken: {:?}", start.elapsed());
 
     println!("----------");
     println!("Part 2: \n----------");
 
     start = Instant::now();
-    part_2(&bags);
+    part_2(&bags, bag);
     println!("Time Taken: {:?}", start.elapsed());
 }